@@ -0,0 +1,123 @@
+use crate::purity::PureFunction;
+use serde::Serialize;
+
+/// Inter-function call graph, pure and impure functions alike
+#[derive(Debug, Default, Serialize)]
+pub struct CallGraph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Node {
+    /// Gene id, e.g. `src/math.ts#add`
+    pub id: String,
+    pub name: String,
+    pub pure: bool,
+    pub impure_reasons: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+}
+
+impl CallGraph {
+    /// Add one file's functions, keyed by id. Calls are resolved by name
+    /// against functions of the same file; unresolved callees are dropped.
+    pub fn add_file(&mut self, functions: &[(String, PureFunction)]) {
+        for (id, func) in functions {
+            self.nodes.push(Node {
+                id: id.clone(),
+                name: func.name.clone(),
+                pure: func.is_pure,
+                impure_reasons: func.impure_reasons.clone(),
+            });
+
+            for callee in &func.calls {
+                if let Some((to, _)) = functions.iter().find(|(_, f)| &f.name == callee) {
+                    self.edges.push(Edge {
+                        from: id.clone(),
+                        to: to.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Graphviz source; pure nodes are green, impure ones red
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph calls {\n");
+        for node in &self.nodes {
+            let color = if node.pure { "palegreen" } else { "lightcoral" };
+            dot.push_str(&format!(
+                "    {} [label={}, style=filled, fillcolor={}];\n",
+                quote(&node.id),
+                quote(&node.name),
+                color
+            ));
+        }
+        for edge in &self.edges {
+            dot.push_str(&format!("    {} -> {};\n", quote(&edge.from), quote(&edge.to)));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::purity::PurityChecker;
+    use crate::test_support::parse_module;
+
+    fn graph(code: &str) -> CallGraph {
+        let functions: Vec<(String, PureFunction)> = PurityChecker::new()
+            .analyze(&parse_module(code))
+            .into_iter()
+            .map(|f| (format!("a.ts#{}", f.qualified_name()), f))
+            .collect();
+
+        let mut graph = CallGraph::default();
+        graph.add_file(&functions);
+        graph
+    }
+
+    #[test]
+    fn test_caller_callee_edge() {
+        let graph = graph(
+            r#"
+            function square(n) {
+                return n * n;
+            }
+
+            function sumOfSquares(a, b) {
+                return square(a) + square(b) + parseInt("0");
+            }
+        "#,
+        );
+
+        assert_eq!(
+            graph.edges,
+            vec![Edge {
+                from: "a.ts#sumOfSquares".to_string(),
+                to: "a.ts#square".to_string(),
+            }]
+        );
+        assert!(graph.nodes[0].pure);
+        assert!(!graph.nodes[1].pure);
+
+        let dot = graph.to_dot();
+        assert!(dot.contains("\"a.ts#sumOfSquares\" -> \"a.ts#square\";"));
+        assert!(dot.contains("\"a.ts#square\" [label=\"square\", style=filled, fillcolor=palegreen];"));
+
+        let json = serde_json::to_value(&graph).unwrap();
+        assert_eq!(json["edges"][0]["from"], "a.ts#sumOfSquares");
+        assert_eq!(json["edges"][0]["to"], "a.ts#square");
+    }
+}
@@ -0,0 +1,197 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Looked up in the scan root so rules are versioned with the code
+pub const CONFIG_FILE: &str = "purity.toml";
+
+/// Team-specific purity rules, layered on top of the built-in ones; the
+/// empty default is exactly the built-in behavior
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PurityConfig {
+    /// Globals whose members are pure, e.g. `fmt` for `fmt.pad(x)`
+    pub pure_namespaces: HashSet<String>,
+    /// Dotted calls that are pure even where their namespace is not, e.g.
+    /// `Date.UTC`; wins over every impure rule
+    pub pure_members: HashSet<String>,
+    /// Namespaces whose every call is a side effect, e.g. `logger`
+    pub impure_namespaces: HashSet<String>,
+    /// Calls that are side effects, plain (`track`) or dotted (`api.send`)
+    #[serde(alias = "impure_functions")]
+    pub impure_calls: HashSet<String>,
+    /// Methods that mutate their receiver, e.g. `push` in `xs.push(1)`
+    pub mutating_methods: HashSet<String>,
+    /// Reject functions that `throw`
+    pub throw_impure: bool,
+    /// Reject generator functions
+    pub generators_impure: bool,
+    /// Reject functions that `await`
+    pub async_impure: bool,
+}
+
+impl Default for PurityConfig {
+    fn default() -> Self {
+        Self {
+            pure_namespaces: HashSet::new(),
+            pure_members: HashSet::new(),
+            impure_namespaces: HashSet::new(),
+            impure_calls: HashSet::new(),
+            mutating_methods: HashSet::new(),
+            throw_impure: false,
+            generators_impure: false,
+            async_impure: true,
+        }
+    }
+}
+
+impl PurityConfig {
+    pub fn from_toml(src: &str) -> Result<Self> {
+        Ok(toml::from_str(src)?)
+    }
+
+    pub fn from_json(src: &str) -> Result<Self> {
+        Ok(serde_json::from_str(src)?)
+    }
+
+    /// An explicit config file: JSON for `.json`, TOML otherwise
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {:?}", path))?;
+        let config = if path.extension().is_some_and(|ext| ext == "json") {
+            Self::from_json(&raw)
+        } else {
+            Self::from_toml(&raw)
+        };
+        config.with_context(|| format!("Invalid purity config {:?}", path))
+    }
+
+    /// `purity.toml` in `root`, or the built-in rules when there is none
+    pub fn load_from_root(root: &Path) -> Result<Self> {
+        let path = root.join(CONFIG_FILE);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        Self::load(&path)
+    }
+
+    pub(crate) fn is_pure_namespace(&self, name: &str) -> bool {
+        self.pure_namespaces.contains(name)
+    }
+
+    pub(crate) fn is_pure_member(&self, name: &str) -> bool {
+        self.pure_members.contains(name)
+    }
+
+    pub(crate) fn is_impure_namespace(&self, name: &str) -> bool {
+        self.impure_namespaces.contains(name)
+    }
+
+    pub(crate) fn is_impure_call(&self, name: &str) -> bool {
+        self.impure_calls.contains(name)
+    }
+
+    pub(crate) fn is_mutating_method(&self, name: &str) -> bool {
+        self.mutating_methods.contains(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::purity::PurityChecker;
+    use crate::test_support::parse_module;
+
+    fn pure_names(code: &str, config: PurityConfig) -> Vec<String> {
+        PurityChecker::new()
+            .with_config(config)
+            .extract_pure_functions(&parse_module(code))
+            .into_iter()
+            .map(|f| f.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_committed_config_changes_classification() {
+        let code = r#"
+            function report(logger, x) {
+                logger.info(x);
+                return x;
+            }
+
+            function padded(s) {
+                return fmt.pad(s, 4);
+            }
+
+            function append(xs, x) {
+                xs.push(x);
+                return xs;
+            }
+
+            function check(x) {
+                if (x < 0) throw "negative";
+                return x;
+            }
+        "#;
+
+        assert_eq!(pure_names(code, PurityConfig::default()), vec!["report", "append", "check"]);
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(CONFIG_FILE),
+            r#"
+                pure_namespaces = ["fmt"]
+                impure_namespaces = ["logger"]
+                mutating_methods = ["push"]
+                throw_impure = true
+            "#,
+        )
+        .unwrap();
+        let config = PurityConfig::load_from_root(dir.path()).unwrap();
+
+        assert_eq!(pure_names(code, config), vec!["padded"]);
+    }
+
+    #[test]
+    fn test_missing_and_invalid_config() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(PurityConfig::load_from_root(dir.path()).unwrap(), PurityConfig::default());
+
+        std::fs::write(dir.path().join(CONFIG_FILE), "pure_namespace = [\"fmt\"]").unwrap();
+        assert!(PurityConfig::load_from_root(dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_pure_members_from_json() {
+        let code = r#"
+            function floor(x) {
+                return Math.floor(x);
+            }
+
+            function round(x) {
+                return Math.round(x);
+            }
+
+            function utc(y, m) {
+                return Date.UTC(y, m);
+            }
+        "#;
+
+        assert_eq!(pure_names(code, PurityConfig::default()), vec!["floor", "round"]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("purity.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "impure_namespaces": ["Math"],
+                "pure_members": ["Math.floor", "Date.UTC"]
+            }"#,
+        )
+        .unwrap();
+        let config = PurityConfig::load(&path).unwrap();
+
+        assert_eq!(pure_names(code, config), vec!["floor", "utc"]);
+    }
+}
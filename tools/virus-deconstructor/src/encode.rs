@@ -0,0 +1,876 @@
+use swc_ecma_ast::*;
+
+/// Version of the canonical encoding below. Hashes are only comparable
+/// between genes with the same `frontend_version`; bump it whenever the
+/// output of `encode_function`/`encode_arrow` changes for any input.
+pub const FRONTEND_VERSION: &str = "canon/2";
+
+/// Stable S-expression encoding of a function, e.g.
+/// `(fn (params (id a) (id b)) (block (return (bin + (id a) (id b)))))`.
+///
+/// The encoding is written out node by node rather than derived from SWC's
+/// serde layout, so dependency upgrades cannot change it. Spans, parentheses,
+/// type annotations and TypeScript-only expressions are not part of it.
+pub fn encode_function(function: &Function) -> String {
+    let mut enc = Encoder::default();
+    enc.function("fn", function);
+    enc.out
+}
+
+pub fn encode_arrow(arrow: &ArrowExpr) -> String {
+    let mut enc = Encoder::default();
+    enc.arrow(arrow);
+    enc.out
+}
+
+pub fn encode_expr(expr: &Expr) -> String {
+    let mut enc = Encoder::default();
+    enc.expr(expr);
+    enc.out
+}
+
+#[derive(Default)]
+struct Encoder {
+    out: String,
+}
+
+impl Encoder {
+    fn sep(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with('(') {
+            self.out.push(' ');
+        }
+    }
+
+    fn node(&mut self, tag: &str, children: impl FnOnce(&mut Self)) {
+        self.sep();
+        self.out.push('(');
+        self.out.push_str(tag);
+        children(self);
+        self.out.push(')');
+    }
+
+    fn atom(&mut self, atom: &str) {
+        self.sep();
+        self.out.push_str(atom);
+    }
+
+    fn flag(&mut self, set: bool, atom: &str) {
+        if set {
+            self.atom(atom);
+        }
+    }
+
+    fn string(&mut self, s: &str) {
+        self.sep();
+        self.out.push_str(&serde_json::to_string(s).unwrap_or_default());
+    }
+
+    fn number(&mut self, n: f64) {
+        self.atom(&n.to_string());
+    }
+
+    fn ident(&mut self, ident: &Ident) {
+        self.node("id", |e| e.atom(&ident.sym));
+    }
+
+    fn opt_expr(&mut self, expr: Option<&Expr>) {
+        match expr {
+            Some(expr) => self.expr(expr),
+            None => self.node("none", |_| {}),
+        }
+    }
+
+    fn function(&mut self, tag: &str, function: &Function) {
+        self.node(tag, |e| {
+            e.flag(function.is_async, "async");
+            e.flag(function.is_generator, "generator");
+            e.decorators(&function.decorators);
+            e.node("params", |e| {
+                for param in &function.params {
+                    e.decorators(&param.decorators);
+                    e.pat(&param.pat);
+                }
+            });
+            match &function.body {
+                Some(body) => e.block(body),
+                None => e.node("none", |_| {}),
+            }
+        });
+    }
+
+    fn arrow(&mut self, arrow: &ArrowExpr) {
+        self.node("arrow", |e| {
+            e.flag(arrow.is_async, "async");
+            e.flag(arrow.is_generator, "generator");
+            e.node("params", |e| {
+                for param in &arrow.params {
+                    e.pat(param);
+                }
+            });
+            match &*arrow.body {
+                BlockStmtOrExpr::BlockStmt(block) => e.block(block),
+                BlockStmtOrExpr::Expr(expr) => e.expr(expr),
+            }
+        });
+    }
+
+    fn decorators(&mut self, decorators: &[Decorator]) {
+        for decorator in decorators {
+            self.node("decorator", |e| e.expr(&decorator.expr));
+        }
+    }
+
+    fn block(&mut self, block: &BlockStmt) {
+        self.node("block", |e| {
+            for stmt in &block.stmts {
+                e.stmt(stmt);
+            }
+        });
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Block(block) => self.block(block),
+            Stmt::Empty(_) => self.node("empty", |_| {}),
+            Stmt::Debugger(_) => self.node("debugger", |_| {}),
+            Stmt::With(with) => self.node("with", |e| {
+                e.expr(&with.obj);
+                e.stmt(&with.body);
+            }),
+            Stmt::Return(ret) => self.node("return", |e| {
+                if let Some(arg) = &ret.arg {
+                    e.expr(arg);
+                }
+            }),
+            Stmt::Labeled(labeled) => self.node("label", |e| {
+                e.atom(&labeled.label.sym);
+                e.stmt(&labeled.body);
+            }),
+            Stmt::Break(brk) => self.node("break", |e| {
+                if let Some(label) = &brk.label {
+                    e.atom(&label.sym);
+                }
+            }),
+            Stmt::Continue(cont) => self.node("continue", |e| {
+                if let Some(label) = &cont.label {
+                    e.atom(&label.sym);
+                }
+            }),
+            Stmt::If(stmt) => self.node("if", |e| {
+                e.expr(&stmt.test);
+                e.stmt(&stmt.cons);
+                if let Some(alt) = &stmt.alt {
+                    e.stmt(alt);
+                }
+            }),
+            Stmt::Switch(switch) => self.node("switch", |e| {
+                e.expr(&switch.discriminant);
+                for case in &switch.cases {
+                    let tag = if case.test.is_some() { "case" } else { "default" };
+                    e.node(tag, |e| {
+                        if let Some(test) = &case.test {
+                            e.expr(test);
+                        }
+                        for stmt in &case.cons {
+                            e.stmt(stmt);
+                        }
+                    });
+                }
+            }),
+            Stmt::Throw(throw) => self.node("throw", |e| e.expr(&throw.arg)),
+            Stmt::Try(stmt) => self.node("try", |e| {
+                e.block(&stmt.block);
+                if let Some(handler) = &stmt.handler {
+                    e.node("catch", |e| {
+                        if let Some(param) = &handler.param {
+                            e.pat(param);
+                        }
+                        e.block(&handler.body);
+                    });
+                }
+                if let Some(finalizer) = &stmt.finalizer {
+                    e.node("finally", |e| e.block(finalizer));
+                }
+            }),
+            Stmt::While(stmt) => self.node("while", |e| {
+                e.expr(&stmt.test);
+                e.stmt(&stmt.body);
+            }),
+            Stmt::DoWhile(stmt) => self.node("do-while", |e| {
+                e.stmt(&stmt.body);
+                e.expr(&stmt.test);
+            }),
+            Stmt::For(stmt) => self.node("for", |e| {
+                match &stmt.init {
+                    Some(VarDeclOrExpr::VarDecl(decl)) => e.var_decl(decl),
+                    Some(VarDeclOrExpr::Expr(expr)) => e.expr(expr),
+                    None => e.node("none", |_| {}),
+                }
+                e.opt_expr(stmt.test.as_deref());
+                e.opt_expr(stmt.update.as_deref());
+                e.stmt(&stmt.body);
+            }),
+            Stmt::ForIn(stmt) => self.node("for-in", |e| {
+                e.for_head(&stmt.left);
+                e.expr(&stmt.right);
+                e.stmt(&stmt.body);
+            }),
+            Stmt::ForOf(stmt) => self.node(if stmt.is_await { "for-await-of" } else { "for-of" }, |e| {
+                e.for_head(&stmt.left);
+                e.expr(&stmt.right);
+                e.stmt(&stmt.body);
+            }),
+            Stmt::Decl(decl) => self.decl(decl),
+            Stmt::Expr(stmt) => self.node("expr", |e| e.expr(&stmt.expr)),
+        }
+    }
+
+    fn for_head(&mut self, head: &ForHead) {
+        match head {
+            ForHead::VarDecl(decl) => self.var_decl(decl),
+            ForHead::UsingDecl(decl) => self.using_decl(decl),
+            ForHead::Pat(pat) => self.pat(pat),
+        }
+    }
+
+    fn decl(&mut self, decl: &Decl) {
+        match decl {
+            Decl::Class(class) => self.class(Some(&class.ident), &class.class),
+            Decl::Fn(func) => self.node("fn-decl", |e| {
+                e.atom(&func.ident.sym);
+                e.function("fn", &func.function);
+            }),
+            Decl::Var(decl) => self.var_decl(decl),
+            Decl::Using(decl) => self.using_decl(decl),
+            // Type-only declarations have no runtime behavior
+            Decl::TsInterface(_) | Decl::TsTypeAlias(_) => {}
+            Decl::TsEnum(decl) => self.node("enum", |e| {
+                e.flag(decl.is_const, "const");
+                e.atom(&decl.id.sym);
+                for member in &decl.members {
+                    e.node("member", |e| {
+                        match &member.id {
+                            TsEnumMemberId::Ident(ident) => e.atom(&ident.sym),
+                            TsEnumMemberId::Str(s) => e.string(&s.value),
+                        }
+                        if let Some(init) = &member.init {
+                            e.expr(init);
+                        }
+                    });
+                }
+            }),
+            Decl::TsModule(_) => self.node("ts-module", |_| {}),
+        }
+    }
+
+    fn var_decl(&mut self, decl: &VarDecl) {
+        let kind = match decl.kind {
+            VarDeclKind::Var => "var",
+            VarDeclKind::Let => "let",
+            VarDeclKind::Const => "const",
+        };
+        self.node(kind, |e| e.declarators(&decl.decls));
+    }
+
+    fn using_decl(&mut self, decl: &UsingDecl) {
+        self.node(if decl.is_await { "await-using" } else { "using" }, |e| e.declarators(&decl.decls));
+    }
+
+    fn declarators(&mut self, decls: &[VarDeclarator]) {
+        for decl in decls {
+            self.node("decl", |e| {
+                e.pat(&decl.name);
+                if let Some(init) = &decl.init {
+                    e.expr(init);
+                }
+            });
+        }
+    }
+
+    fn pat(&mut self, pat: &Pat) {
+        match pat {
+            Pat::Ident(ident) => self.ident(&ident.id),
+            Pat::Array(array) => self.array_pat(array),
+            Pat::Rest(rest) => self.node("rest", |e| e.pat(&rest.arg)),
+            Pat::Object(object) => self.object_pat(object),
+            Pat::Assign(assign) => self.node("default", |e| {
+                e.pat(&assign.left);
+                e.expr(&assign.right);
+            }),
+            Pat::Invalid(_) => self.node("invalid", |_| {}),
+            Pat::Expr(expr) => self.expr(expr),
+        }
+    }
+
+    fn array_pat(&mut self, array: &ArrayPat) {
+        self.node("array-pat", |e| {
+            for elem in &array.elems {
+                match elem {
+                    Some(pat) => e.pat(pat),
+                    None => e.node("hole", |_| {}),
+                }
+            }
+        });
+    }
+
+    fn object_pat(&mut self, object: &ObjectPat) {
+        self.node("object-pat", |e| {
+            for prop in &object.props {
+                match prop {
+                    ObjectPatProp::KeyValue(kv) => e.node("kv", |e| {
+                        e.prop_name(&kv.key);
+                        e.pat(&kv.value);
+                    }),
+                    ObjectPatProp::Assign(assign) => e.node("assign", |e| {
+                        e.ident(&assign.key.id);
+                        if let Some(value) = &assign.value {
+                            e.expr(value);
+                        }
+                    }),
+                    ObjectPatProp::Rest(rest) => e.node("rest", |e| e.pat(&rest.arg)),
+                }
+            }
+        });
+    }
+
+    fn prop_name(&mut self, name: &PropName) {
+        match name {
+            PropName::Ident(ident) => self.node("name", |e| e.atom(&ident.sym)),
+            PropName::Str(s) => self.node("str", |e| e.string(&s.value)),
+            PropName::Num(n) => self.node("num", |e| e.number(n.value)),
+            PropName::BigInt(b) => self.node("bigint", |e| e.atom(&b.value.to_string())),
+            PropName::Computed(computed) => self.node("computed", |e| e.expr(&computed.expr)),
+        }
+    }
+
+    fn lit(&mut self, lit: &Lit) {
+        match lit {
+            Lit::Str(s) => self.node("str", |e| e.string(&s.value)),
+            Lit::Bool(b) => self.node("bool", |e| e.atom(if b.value { "true" } else { "false" })),
+            Lit::Null(_) => self.node("null", |_| {}),
+            Lit::Num(n) => self.node("num", |e| e.number(n.value)),
+            Lit::BigInt(b) => self.node("bigint", |e| e.atom(&b.value.to_string())),
+            Lit::Regex(re) => self.node("regex", |e| {
+                e.string(&re.exp);
+                e.string(&re.flags);
+            }),
+            Lit::JSXText(text) => self.node("jsx-text", |e| e.string(&text.value)),
+        }
+    }
+
+    fn args(&mut self, args: &[ExprOrSpread]) {
+        for arg in args {
+            self.expr_or_spread(arg);
+        }
+    }
+
+    fn expr_or_spread(&mut self, arg: &ExprOrSpread) {
+        match arg.spread {
+            Some(_) => self.node("spread", |e| e.expr(&arg.expr)),
+            None => self.expr(&arg.expr),
+        }
+    }
+
+    fn member_prop(&mut self, prop: &MemberProp) {
+        match prop {
+            MemberProp::Ident(ident) => self.node("name", |e| e.atom(&ident.sym)),
+            MemberProp::PrivateName(name) => self.node("private", |e| e.atom(&name.id.sym)),
+            MemberProp::Computed(computed) => self.node("computed", |e| e.expr(&computed.expr)),
+        }
+    }
+
+    fn member(&mut self, member: &MemberExpr) {
+        self.node("member", |e| {
+            e.expr(&member.obj);
+            e.member_prop(&member.prop);
+        });
+    }
+
+    fn super_prop(&mut self, prop: &SuperPropExpr) {
+        self.node("super-member", |e| match &prop.prop {
+            SuperProp::Ident(ident) => e.node("name", |e| e.atom(&ident.sym)),
+            SuperProp::Computed(computed) => e.node("computed", |e| e.expr(&computed.expr)),
+        });
+    }
+
+    fn callee(&mut self, callee: &Callee) {
+        match callee {
+            Callee::Super(_) => self.node("super", |_| {}),
+            Callee::Import(_) => self.node("import", |_| {}),
+            Callee::Expr(expr) => self.expr(expr),
+        }
+    }
+
+    fn opt_chain(&mut self, chain: &OptChainExpr) {
+        self.node("opt-chain", |e| {
+            e.flag(chain.optional, "optional");
+            match &*chain.base {
+                OptChainBase::Member(member) => e.member(member),
+                OptChainBase::Call(call) => e.node("call", |e| {
+                    e.expr(&call.callee);
+                    e.args(&call.args);
+                }),
+            }
+        });
+    }
+
+    fn assign_target(&mut self, target: &AssignTarget) {
+        match target {
+            AssignTarget::Simple(simple) => match simple {
+                SimpleAssignTarget::Ident(ident) => self.ident(&ident.id),
+                SimpleAssignTarget::Member(member) => self.member(member),
+                SimpleAssignTarget::SuperProp(prop) => self.super_prop(prop),
+                SimpleAssignTarget::Paren(paren) => self.expr(&paren.expr),
+                SimpleAssignTarget::OptChain(chain) => self.opt_chain(chain),
+                SimpleAssignTarget::TsAs(ts) => self.expr(&ts.expr),
+                SimpleAssignTarget::TsSatisfies(ts) => self.expr(&ts.expr),
+                SimpleAssignTarget::TsNonNull(ts) => self.expr(&ts.expr),
+                SimpleAssignTarget::TsTypeAssertion(ts) => self.expr(&ts.expr),
+                SimpleAssignTarget::TsInstantiation(ts) => self.expr(&ts.expr),
+                SimpleAssignTarget::Invalid(_) => self.node("invalid", |_| {}),
+            },
+            AssignTarget::Pat(pat) => match pat {
+                AssignTargetPat::Array(array) => self.array_pat(array),
+                AssignTargetPat::Object(object) => self.object_pat(object),
+                AssignTargetPat::Invalid(_) => self.node("invalid", |_| {}),
+            },
+        }
+    }
+
+    fn tpl(&mut self, tpl: &Tpl) {
+        self.node("tpl", |e| {
+            for (i, quasi) in tpl.quasis.iter().enumerate() {
+                e.string(&quasi.raw);
+                if let Some(expr) = tpl.exprs.get(i) {
+                    e.expr(expr);
+                }
+            }
+        });
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::This(_) => self.node("this", |_| {}),
+            Expr::Array(array) => self.node("array", |e| {
+                for elem in &array.elems {
+                    match elem {
+                        Some(elem) => e.expr_or_spread(elem),
+                        None => e.node("hole", |_| {}),
+                    }
+                }
+            }),
+            Expr::Object(object) => self.node("object", |e| {
+                for prop in &object.props {
+                    match prop {
+                        PropOrSpread::Spread(spread) => e.node("spread", |e| e.expr(&spread.expr)),
+                        PropOrSpread::Prop(prop) => e.prop(prop),
+                    }
+                }
+            }),
+            Expr::Fn(func) => self.node("fn-expr", |e| {
+                if let Some(ident) = &func.ident {
+                    e.atom(&ident.sym);
+                }
+                e.function("fn", &func.function);
+            }),
+            Expr::Unary(unary) => self.node("unary", |e| {
+                e.atom(unary_op(unary.op));
+                e.expr(&unary.arg);
+            }),
+            Expr::Update(update) => self.node("update", |e| {
+                e.atom(match update.op {
+                    UpdateOp::PlusPlus => "++",
+                    UpdateOp::MinusMinus => "--",
+                });
+                e.atom(if update.prefix { "prefix" } else { "postfix" });
+                e.expr(&update.arg);
+            }),
+            Expr::Bin(bin) => self.node("bin", |e| {
+                e.atom(binary_op(bin.op));
+                e.expr(&bin.left);
+                e.expr(&bin.right);
+            }),
+            Expr::Assign(assign) => self.node("assign", |e| {
+                e.atom(assign_op(assign.op));
+                e.assign_target(&assign.left);
+                e.expr(&assign.right);
+            }),
+            Expr::Member(member) => self.member(member),
+            Expr::SuperProp(prop) => self.super_prop(prop),
+            Expr::Cond(cond) => self.node("cond", |e| {
+                e.expr(&cond.test);
+                e.expr(&cond.cons);
+                e.expr(&cond.alt);
+            }),
+            Expr::Call(call) => self.node("call", |e| {
+                e.callee(&call.callee);
+                e.args(&call.args);
+            }),
+            Expr::New(new) => self.node("new", |e| {
+                e.expr(&new.callee);
+                if let Some(args) = &new.args {
+                    e.args(args);
+                }
+            }),
+            Expr::Seq(seq) => self.node("seq", |e| {
+                for expr in &seq.exprs {
+                    e.expr(expr);
+                }
+            }),
+            Expr::Ident(ident) => self.ident(ident),
+            Expr::Lit(lit) => self.lit(lit),
+            Expr::Tpl(tpl) => self.tpl(tpl),
+            Expr::TaggedTpl(tagged) => self.node("tagged", |e| {
+                e.expr(&tagged.tag);
+                e.tpl(&tagged.tpl);
+            }),
+            Expr::Arrow(arrow) => self.arrow(arrow),
+            Expr::Class(class) => self.class(class.ident.as_ref(), &class.class),
+            Expr::Yield(yield_expr) => self.node("yield", |e| {
+                e.flag(yield_expr.delegate, "delegate");
+                if let Some(arg) = &yield_expr.arg {
+                    e.expr(arg);
+                }
+            }),
+            Expr::MetaProp(meta) => self.node("meta", |e| {
+                e.atom(match meta.kind {
+                    MetaPropKind::NewTarget => "new.target",
+                    MetaPropKind::ImportMeta => "import.meta",
+                });
+            }),
+            Expr::Await(await_expr) => self.node("await", |e| e.expr(&await_expr.arg)),
+            // The tree already encodes precedence
+            Expr::Paren(paren) => self.expr(&paren.expr),
+            Expr::JSXMember(member) => self.jsx_member(member),
+            Expr::JSXNamespacedName(name) => self.jsx_namespaced(name),
+            Expr::JSXEmpty(_) => self.node("jsx-empty", |_| {}),
+            Expr::JSXElement(element) => self.jsx_element(element),
+            Expr::JSXFragment(fragment) => self.jsx_fragment(fragment),
+            Expr::TsTypeAssertion(ts) => self.expr(&ts.expr),
+            Expr::TsConstAssertion(ts) => self.expr(&ts.expr),
+            Expr::TsNonNull(ts) => self.expr(&ts.expr),
+            Expr::TsAs(ts) => self.expr(&ts.expr),
+            Expr::TsInstantiation(ts) => self.expr(&ts.expr),
+            Expr::TsSatisfies(ts) => self.expr(&ts.expr),
+            Expr::PrivateName(name) => self.node("private", |e| e.atom(&name.id.sym)),
+            Expr::OptChain(chain) => self.opt_chain(chain),
+            Expr::Invalid(_) => self.node("invalid", |_| {}),
+        }
+    }
+
+    fn prop(&mut self, prop: &Prop) {
+        match prop {
+            Prop::Shorthand(ident) => self.node("shorthand", |e| e.ident(ident)),
+            Prop::KeyValue(kv) => self.node("kv", |e| {
+                e.prop_name(&kv.key);
+                e.expr(&kv.value);
+            }),
+            Prop::Assign(assign) => self.node("assign", |e| {
+                e.ident(&assign.key);
+                e.expr(&assign.value);
+            }),
+            Prop::Getter(getter) => self.node("get", |e| {
+                e.prop_name(&getter.key);
+                match &getter.body {
+                    Some(body) => e.block(body),
+                    None => e.node("none", |_| {}),
+                }
+            }),
+            Prop::Setter(setter) => self.node("set", |e| {
+                e.prop_name(&setter.key);
+                e.pat(&setter.param);
+                match &setter.body {
+                    Some(body) => e.block(body),
+                    None => e.node("none", |_| {}),
+                }
+            }),
+            Prop::Method(method) => self.node("method", |e| {
+                e.prop_name(&method.key);
+                e.function("fn", &method.function);
+            }),
+        }
+    }
+
+    fn class(&mut self, ident: Option<&Ident>, class: &Class) {
+        self.node("class", |e| {
+            if let Some(ident) = ident {
+                e.atom(&ident.sym);
+            }
+            e.decorators(&class.decorators);
+            if let Some(super_class) = &class.super_class {
+                e.node("extends", |e| e.expr(super_class));
+            }
+            for member in &class.body {
+                e.class_member(member);
+            }
+        });
+    }
+
+    fn class_member(&mut self, member: &ClassMember) {
+        match member {
+            ClassMember::Constructor(ctor) => self.node("constructor", |e| {
+                e.node("params", |e| {
+                    for param in &ctor.params {
+                        match param {
+                            ParamOrTsParamProp::Param(param) => {
+                                e.decorators(&param.decorators);
+                                e.pat(&param.pat);
+                            }
+                            ParamOrTsParamProp::TsParamProp(prop) => e.node("param-prop", |e| {
+                                e.decorators(&prop.decorators);
+                                match &prop.param {
+                                    TsParamPropParam::Ident(ident) => e.ident(&ident.id),
+                                    TsParamPropParam::Assign(assign) => e.node("default", |e| {
+                                        e.pat(&assign.left);
+                                        e.expr(&assign.right);
+                                    }),
+                                }
+                            }),
+                        }
+                    }
+                });
+                match &ctor.body {
+                    Some(body) => e.block(body),
+                    None => e.node("none", |_| {}),
+                }
+            }),
+            ClassMember::Method(method) => self.node("method", |e| {
+                e.atom(method_kind(method.kind));
+                e.flag(method.is_static, "static");
+                e.prop_name(&method.key);
+                e.function("fn", &method.function);
+            }),
+            ClassMember::PrivateMethod(method) => self.node("private-method", |e| {
+                e.atom(method_kind(method.kind));
+                e.flag(method.is_static, "static");
+                e.atom(&method.key.id.sym);
+                e.function("fn", &method.function);
+            }),
+            ClassMember::ClassProp(prop) => self.node("prop", |e| {
+                e.flag(prop.is_static, "static");
+                e.decorators(&prop.decorators);
+                e.prop_name(&prop.key);
+                if let Some(value) = &prop.value {
+                    e.expr(value);
+                }
+            }),
+            ClassMember::PrivateProp(prop) => self.node("private-prop", |e| {
+                e.flag(prop.is_static, "static");
+                e.decorators(&prop.decorators);
+                e.atom(&prop.key.id.sym);
+                if let Some(value) = &prop.value {
+                    e.expr(value);
+                }
+            }),
+            ClassMember::TsIndexSignature(_) | ClassMember::Empty(_) => {}
+            ClassMember::StaticBlock(block) => self.node("static-block", |e| e.block(&block.body)),
+            ClassMember::AutoAccessor(accessor) => self.node("accessor", |e| {
+                e.flag(accessor.is_static, "static");
+                match &accessor.key {
+                    Key::Private(name) => e.node("private", |e| e.atom(&name.id.sym)),
+                    Key::Public(name) => e.prop_name(name),
+                }
+                if let Some(value) = &accessor.value {
+                    e.expr(value);
+                }
+            }),
+        }
+    }
+
+    fn jsx_member(&mut self, member: &JSXMemberExpr) {
+        self.node("jsx-member", |e| {
+            match &member.obj {
+                JSXObject::Ident(ident) => e.ident(ident),
+                JSXObject::JSXMemberExpr(inner) => e.jsx_member(inner),
+            }
+            e.atom(&member.prop.sym);
+        });
+    }
+
+    fn jsx_namespaced(&mut self, name: &JSXNamespacedName) {
+        self.node("jsx-ns", |e| {
+            e.atom(&name.ns.sym);
+            e.atom(&name.name.sym);
+        });
+    }
+
+    fn jsx_element_name(&mut self, name: &JSXElementName) {
+        match name {
+            JSXElementName::Ident(ident) => self.ident(ident),
+            JSXElementName::JSXMemberExpr(member) => self.jsx_member(member),
+            JSXElementName::JSXNamespacedName(name) => self.jsx_namespaced(name),
+        }
+    }
+
+    fn jsx_expr_container(&mut self, container: &JSXExprContainer) {
+        match &container.expr {
+            JSXExpr::JSXEmptyExpr(_) => self.node("jsx-empty", |_| {}),
+            JSXExpr::Expr(expr) => self.expr(expr),
+        }
+    }
+
+    fn jsx_element(&mut self, element: &JSXElement) {
+        self.node("jsx-element", |e| {
+            e.jsx_element_name(&element.opening.name);
+            e.node("attrs", |e| {
+                for attr in &element.opening.attrs {
+                    match attr {
+                        JSXAttrOrSpread::SpreadElement(spread) => e.node("spread", |e| e.expr(&spread.expr)),
+                        JSXAttrOrSpread::JSXAttr(attr) => e.node("attr", |e| {
+                            match &attr.name {
+                                JSXAttrName::Ident(ident) => e.atom(&ident.sym),
+                                JSXAttrName::JSXNamespacedName(name) => e.jsx_namespaced(name),
+                            }
+                            match &attr.value {
+                                Some(JSXAttrValue::Lit(lit)) => e.lit(lit),
+                                Some(JSXAttrValue::JSXExprContainer(container)) => e.jsx_expr_container(container),
+                                Some(JSXAttrValue::JSXElement(element)) => e.jsx_element(element),
+                                Some(JSXAttrValue::JSXFragment(fragment)) => e.jsx_fragment(fragment),
+                                None => {}
+                            }
+                        }),
+                    }
+                }
+            });
+            e.jsx_children(&element.children);
+        });
+    }
+
+    fn jsx_fragment(&mut self, fragment: &JSXFragment) {
+        self.node("jsx-fragment", |e| e.jsx_children(&fragment.children));
+    }
+
+    fn jsx_children(&mut self, children: &[JSXElementChild]) {
+        for child in children {
+            match child {
+                JSXElementChild::JSXText(text) => self.node("jsx-text", |e| e.string(&text.value)),
+                JSXElementChild::JSXExprContainer(container) => self.jsx_expr_container(container),
+                JSXElementChild::JSXSpreadChild(spread) => self.node("spread", |e| e.expr(&spread.expr)),
+                JSXElementChild::JSXElement(element) => self.jsx_element(element),
+                JSXElementChild::JSXFragment(fragment) => self.jsx_fragment(fragment),
+            }
+        }
+    }
+}
+
+fn method_kind(kind: MethodKind) -> &'static str {
+    match kind {
+        MethodKind::Method => "method",
+        MethodKind::Getter => "get",
+        MethodKind::Setter => "set",
+    }
+}
+
+fn unary_op(op: UnaryOp) -> &'static str {
+    match op {
+        UnaryOp::Minus => "-",
+        UnaryOp::Plus => "+",
+        UnaryOp::Bang => "!",
+        UnaryOp::Tilde => "~",
+        UnaryOp::TypeOf => "typeof",
+        UnaryOp::Void => "void",
+        UnaryOp::Delete => "delete",
+    }
+}
+
+fn binary_op(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::EqEq => "==",
+        BinaryOp::NotEq => "!=",
+        BinaryOp::EqEqEq => "===",
+        BinaryOp::NotEqEq => "!==",
+        BinaryOp::Lt => "<",
+        BinaryOp::LtEq => "<=",
+        BinaryOp::Gt => ">",
+        BinaryOp::GtEq => ">=",
+        BinaryOp::LShift => "<<",
+        BinaryOp::RShift => ">>",
+        BinaryOp::ZeroFillRShift => ">>>",
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Mod => "%",
+        BinaryOp::BitOr => "|",
+        BinaryOp::BitXor => "^",
+        BinaryOp::BitAnd => "&",
+        BinaryOp::LogicalOr => "||",
+        BinaryOp::LogicalAnd => "&&",
+        BinaryOp::In => "in",
+        BinaryOp::InstanceOf => "instanceof",
+        BinaryOp::Exp => "**",
+        BinaryOp::NullishCoalescing => "??",
+    }
+}
+
+fn assign_op(op: AssignOp) -> &'static str {
+    match op {
+        AssignOp::Assign => "=",
+        AssignOp::AddAssign => "+=",
+        AssignOp::SubAssign => "-=",
+        AssignOp::MulAssign => "*=",
+        AssignOp::DivAssign => "/=",
+        AssignOp::ModAssign => "%=",
+        AssignOp::LShiftAssign => "<<=",
+        AssignOp::RShiftAssign => ">>=",
+        AssignOp::ZeroFillRShiftAssign => ">>>=",
+        AssignOp::BitOrAssign => "|=",
+        AssignOp::BitXorAssign => "^=",
+        AssignOp::BitAndAssign => "&=",
+        AssignOp::ExpAssign => "**=",
+        AssignOp::AndAssign => "&&=",
+        AssignOp::OrAssign => "||=",
+        AssignOp::NullishAssign => "??=",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::normalize::canonical_form;
+    use crate::normalize::RenameStrategy;
+    use crate::purity::PurityChecker;
+    use crate::test_support::parse_module;
+
+    fn canonical(code: &str) -> String {
+        let module = parse_module(code);
+        let func = &PurityChecker::new().analyze(&module)[0];
+        canonical_form(&module, func.span, RenameStrategy::Full).unwrap()
+    }
+
+    // These strings are the contract behind every stored hash. If one of
+    // them has to change, bump FRONTEND_VERSION in the same commit.
+    #[test]
+    fn test_encoding_is_stable() {
+        let golden = [
+            (
+                "function add(a: number, b: number): number { return a + b; }",
+                "fn:(fn (params (id #0) (id #1)) (block (return (bin + (id #0) (id #1)))))",
+            ),
+            (
+                "const clamp = (x, lo, hi) => Math.min(Math.max(x, lo), hi);",
+                "arrow:(arrow (params (id #0) (id #1) (id #2)) \
+                 (call (member (id Math) (name min)) (call (member (id Math) (name max)) (id #0) (id #1)) (id #2)))",
+            ),
+            (
+                "function total(xs) { let t = 0; for (const x of xs) { t += x; } return t; }",
+                "fn:(fn (params (id #0)) (block (let (decl (id #1) (num 0))) \
+                 (for-of (const (decl (id #2))) (id #0) (block (expr (assign += (id #1) (id #2))))) (return (id #1))))",
+            ),
+            (
+                "function pick(o) { const { a, b = 1 } = o; return [a, b, `${a}!`] as const; }",
+                "fn:(fn (params (id #0)) (block (const (decl (object-pat (kv (name a) (id #1)) \
+                 (kv (name b) (default (id #2) (num 1)))) (id #0))) \
+                 (return (array (id #1) (id #2) (tpl \"\" (id #1) \"!\")))))",
+            ),
+            (
+                "function label(n) { switch (n) { case 1: return 'one'; default: return n > 1 ? \"many\" : null; } }",
+                "fn:(fn (params (id #0)) (block (switch (id #0) (case (num 1) (return (str \"one\"))) \
+                 (default (return (cond (bin > (id #0) (num 1)) (str \"many\") (null)))))))",
+            ),
+        ];
+
+        for (code, expected) in golden {
+            assert_eq!(canonical(code), expected, "{}", code);
+        }
+    }
+}
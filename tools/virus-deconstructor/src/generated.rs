@@ -0,0 +1,76 @@
+/// Header markers that code generators conventionally emit
+pub const DEFAULT_MARKERS: &[&str] = &["@generated", "DO NOT EDIT"];
+
+/// Only the first few lines are searched for a marker
+const HEADER_LINES: usize = 5;
+
+/// Hand-written code rarely has lines this long; bundles and minified chunks do
+const MAX_LINE_LEN: usize = 1000;
+
+/// Below this share of whitespace a large file is treated as minified
+const MIN_WHITESPACE_RATIO: f64 = 0.05;
+const DENSITY_MIN_LEN: usize = 4096;
+
+/// Heuristic check for machine-generated sources: a generator marker in the
+/// header, an extremely long line, or a large file with almost no whitespace.
+pub fn is_generated<S: AsRef<str>>(content: &str, markers: &[S]) -> bool {
+    let header_marked = content
+        .lines()
+        .take(HEADER_LINES)
+        .any(|line| markers.iter().any(|m| line.contains(m.as_ref())));
+    if header_marked {
+        return true;
+    }
+
+    if content.lines().any(|line| line.chars().count() > MAX_LINE_LEN) {
+        return true;
+    }
+
+    // In chars, not bytes, so non-ASCII text is not counted as dense
+    let len = content.chars().count();
+    if len >= DENSITY_MIN_LEN {
+        let whitespace = content.chars().filter(|c| c.is_whitespace()).count();
+        return (whitespace as f64) / (len as f64) < MIN_WHITESPACE_RATIO;
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markers_in_header() {
+        assert!(is_generated("// @generated by protoc\nexport function f() {}", DEFAULT_MARKERS));
+        assert!(is_generated("/* Code generated. DO NOT EDIT. */\n", DEFAULT_MARKERS));
+        assert!(!is_generated("export function f() {}\n", DEFAULT_MARKERS));
+
+        // Custom markers replace the defaults
+        assert!(is_generated("// autogen\n", &["autogen"]));
+        assert!(!is_generated("// @generated\n", &["autogen"]));
+
+        // A marker far below the header is just a mention
+        let late = format!("{}// @generated\n", "const x = 1;\n".repeat(10));
+        assert!(!is_generated(&late, DEFAULT_MARKERS));
+    }
+
+    #[test]
+    fn test_minified_content() {
+        let long_line = format!("var a={};", "1+".repeat(MAX_LINE_LEN));
+        assert!(is_generated(&long_line, DEFAULT_MARKERS));
+
+        let dense = "a=b;c=d;e=f;g=h;i=j;k=l;\n".repeat(200);
+        assert!(is_generated(&dense, DEFAULT_MARKERS));
+
+        let normal = "function add(a, b) {\n    return a + b;\n}\n".repeat(200);
+        assert!(!is_generated(&normal, DEFAULT_MARKERS));
+
+        // 7% whitespace by chars, but under 4% by bytes
+        let cyrillic = format!("//{}\n", "абвгдежзийклмно ".repeat(5)).repeat(60);
+        assert!(!is_generated(&cyrillic, DEFAULT_MARKERS));
+        // Under MAX_LINE_LEN chars, over it in bytes
+        let wide = format!("// {}\n", "Привіт ".repeat(MAX_LINE_LEN / 8));
+        assert!(!is_generated(&wide, DEFAULT_MARKERS));
+    }
+}
@@ -0,0 +1,12 @@
+pub mod callgraph;
+pub mod config;
+pub mod encode;
+pub mod generated;
+pub mod markdown;
+pub mod merkle;
+pub mod normalize;
+pub mod purity;
+#[cfg(test)]
+mod test_support;
+pub mod tsconfig;
+pub mod types;
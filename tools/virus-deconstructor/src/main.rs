@@ -1,15 +1,22 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
+use ignore::{overrides::OverrideBuilder, WalkBuilder};
 use serde::{Deserialize, Serialize};
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use swc_common::{sync::Lrc, SourceMap};
+use swc_common::{sync::Lrc, SourceMap, Spanned};
 use swc_ecma_parser::{lexer::Lexer, Parser as SwcParser, StringInput, Syntax};
-use tracing::{info, warn};
-use walkdir::WalkDir;
+use tracing::{debug, info, warn};
 
-mod purity;
-use purity::PurityChecker;
+use virus_deconstructor::callgraph::CallGraph;
+use virus_deconstructor::config::{self, PurityConfig};
+use virus_deconstructor::encode;
+use virus_deconstructor::generated;
+use virus_deconstructor::markdown;
+use virus_deconstructor::merkle::{self, ModuleHash};
+use virus_deconstructor::normalize::{self, RenameStrategy};
+use virus_deconstructor::purity::{self, PurityChecker};
+use virus_deconstructor::tsconfig::ParserOptions;
 
 #[derive(Parser)]
 #[command(name = "virus-deconstructor")]
@@ -36,23 +43,119 @@ enum Commands {
         #[arg(long, default_value = "true")]
         typescript: bool,
         
+        /// Also scan ts/js code fences in Markdown (.md, .mdx) files
+        #[arg(long)]
+        markdown: bool,
+        
         /// Max depth for directory traversal
         #[arg(long, default_value = "10")]
         max_depth: usize,
+        
+        /// Walk everything: ignore .gitignore and the default skip list
+        #[arg(long)]
+        no_ignore: bool,
+        
+        /// Skip paths matching this glob (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
+        
+        /// Skip machine-generated files (generator header, minified content)
+        #[arg(long)]
+        exclude_generated: bool,
+        
+        /// Header marker for --exclude-generated (repeatable; replaces the
+        /// defaults `@generated` and `DO NOT EDIT`)
+        #[arg(long)]
+        generated_marker: Vec<String>,
+        
+        /// Emit normalized_body_hash (alpha-renamed, commutatively sorted body)
+        #[arg(long)]
+        hash_bodies_normalized: bool,
+        
+        /// How normalized_body_hash treats bound names: full, hashed or none
+        #[arg(long, default_value = "full", requires = "hash_bodies_normalized")]
+        rename_strategy: RenameStrategy,
+        
+        /// tsconfig.json whose experimentalDecorators/target drive parsing
+        #[arg(long)]
+        tsconfig: Option<PathBuf>,
+        
+        /// Treat single-`return` bodies as pure without external-ref analysis
+        #[arg(long)]
+        heuristic: bool,
+        
+        /// Only extract exported functions (the module's public API)
+        #[arg(long)]
+        exports_only: bool,
+        
+        /// Write the call graph here: DOT for a `.dot` path, JSON otherwise
+        #[arg(long)]
+        call_graph: Option<PathBuf>,
+        
+        /// Write one Merkle module hash per scanned file here (NDJSON)
+        #[arg(long)]
+        module_hashes: Option<PathBuf>,
+        
+        /// Purity rules file (JSON, or TOML); replaces `purity.toml` in the root
+        #[arg(long)]
+        purity_config: Option<PathBuf>,
+        
+        /// Abort with an error on the first file that fails to scan
+        #[arg(long)]
+        fail_fast: bool,
     },
 }
 
+struct ScanOptions {
+    typescript: bool,
+    markdown: bool,
+    max_depth: usize,
+    no_ignore: bool,
+    exclude: Vec<String>,
+    /// Markers for skipping generated files; `None` scans them
+    generated_markers: Option<Vec<String>>,
+    hash_bodies_normalized: bool,
+    rename_strategy: RenameStrategy,
+    heuristic: bool,
+    exports_only: bool,
+    /// Rules from `purity.toml` in the scan root
+    config: PurityConfig,
+    parser: ParserOptions,
+    call_graph: Option<PathBuf>,
+    module_hashes: Option<PathBuf>,
+    fail_fast: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct GenManifest {
     name: String,
+    /// Occurrence key: path, scope path and name; unlike `hash` never shared
+    id: String,
     hash: String,
     ast_hash: String,
+    /// Encoding behind `ast_hash`/`normalized_body_hash`; compare only within one
+    frontend_version: String,
     path: String,
     line: u32,
     pure: bool,
     params: Vec<String>,
+    param_types: Vec<Option<String>>,
     return_type: Option<String>,
     body: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    normalized_body_hash: Option<String>,
+}
+
+/// One `--module-hashes` line
+#[derive(Debug, Serialize)]
+struct ModuleRecord {
+    /// The file, or `file#block-N` for a Markdown fence
+    unit: String,
+    path: String,
+    frontend_version: String,
+    /// Leaves are the `ast_hash` of every function in the unit, pure or not
+    #[serde(flatten)]
+    hash: ModuleHash,
 }
 
 fn main() -> Result<()> {
@@ -61,41 +164,103 @@ fn main() -> Result<()> {
     let cli = Cli::parse();
     
     match cli.command {
-        Commands::Scan { root, out, typescript, max_depth } => {
-            scan_directory(&root, &out, typescript, max_depth)?;
+        Commands::Scan {
+            root,
+            out,
+            typescript,
+            markdown,
+            max_depth,
+            no_ignore,
+            exclude,
+            exclude_generated,
+            generated_marker,
+            hash_bodies_normalized,
+            rename_strategy,
+            tsconfig,
+            heuristic,
+            exports_only,
+            call_graph,
+            module_hashes,
+            purity_config,
+            fail_fast,
+        } => {
+            let parser = match tsconfig {
+                Some(path) => ParserOptions::from_tsconfig(&path)?,
+                None => ParserOptions::default(),
+            };
+            let generated_markers = exclude_generated.then(|| {
+                if generated_marker.is_empty() {
+                    generated::DEFAULT_MARKERS.iter().map(|m| m.to_string()).collect()
+                } else {
+                    generated_marker
+                }
+            });
+            let config = match &purity_config {
+                Some(path) => {
+                    info!("Using purity rules from {:?}", path);
+                    PurityConfig::load(path)?
+                }
+                None => {
+                    let config = PurityConfig::load_from_root(&root)?;
+                    if config != PurityConfig::default() {
+                        info!("Using purity rules from {:?}", root.join(config::CONFIG_FILE));
+                    }
+                    config
+                }
+            };
+            let options = ScanOptions {
+                typescript,
+                markdown,
+                max_depth,
+                no_ignore,
+                exclude,
+                generated_markers,
+                hash_bodies_normalized,
+                rename_strategy,
+                heuristic,
+                exports_only,
+                config,
+                parser,
+                call_graph,
+                module_hashes,
+                fail_fast,
+            };
+            scan_directory(&root, &out, &options)?;
         }
     }
     
     Ok(())
 }
 
-fn scan_directory(root: &Path, out: &Path, typescript: bool, max_depth: usize) -> Result<()> {
+fn scan_directory(root: &Path, out: &Path, options: &ScanOptions) -> Result<()> {
     info!("🦠 Virus-Deconstructor starting scan...");
     info!("Root: {:?}", root);
     
     let mut genes = Vec::new();
+    let mut graph = CallGraph::default();
+    let mut modules = Vec::new();
     let cm: Lrc<SourceMap> = Default::default();
     
-    for entry in WalkDir::new(root)
-        .max_depth(max_depth)
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
+    for entry in walker(root, options)?.filter_map(|e| e.ok()) {
         let path = entry.path();
         
         // Check if it's a JS/TS file
-        if !is_js_ts_file(path, typescript) {
+        let is_doc = options.markdown && markdown::is_markdown(path);
+        if !is_js_ts_file(path, options.typescript) && !is_doc {
             continue;
         }
         
         info!("Scanning: {:?}", path);
         
-        match scan_file(&cm, path) {
+        match scan_file(&cm, path, options, &mut graph, &mut modules) {
             Ok(mut file_genes) => {
                 genes.append(&mut file_genes);
             }
+            Err(e) if options.fail_fast => {
+                return Err(e.context(format!("Failed to scan {:?}", path)));
+            }
             Err(e) => {
-                warn!("Failed to scan {:?}: {}", path, e);
+                warn!("Failed to scan {:?}: {:#}", path, e);
             }
         }
     }
@@ -111,9 +276,53 @@ fn scan_directory(root: &Path, out: &Path, typescript: bool, max_depth: usize) -
     
     info!("✅ Manifest written to: {:?}", out);
     
+    if let Some(path) = &options.call_graph {
+        let contents = if path.extension().is_some_and(|ext| ext == "dot") {
+            graph.to_dot()
+        } else {
+            serde_json::to_string_pretty(&graph)?
+        };
+        std::fs::write(path, contents)?;
+        info!("Call graph written to: {:?}", path);
+    }
+    
+    if let Some(path) = &options.module_hashes {
+        let mut file = std::fs::File::create(path)?;
+        for module in &modules {
+            writeln!(file, "{}", serde_json::to_string(module)?)?;
+        }
+        info!("Module hashes written to: {:?}", path);
+    }
+    
     Ok(())
 }
 
+/// Vendored and generated directories skipped unless `--no-ignore`
+const DEFAULT_SKIP: &[&str] = &["node_modules", ".git", "dist", "build", "coverage"];
+
+fn walker(root: &Path, options: &ScanOptions) -> Result<ignore::Walk> {
+    let mut overrides = OverrideBuilder::new(root);
+    if !options.no_ignore {
+        for dir in DEFAULT_SKIP {
+            overrides.add(&format!("!**/{}/", dir))?;
+        }
+    }
+    for glob in &options.exclude {
+        overrides.add(&format!("!{}", glob))?;
+    }
+    
+    let walk = WalkBuilder::new(root)
+        .max_depth(Some(options.max_depth))
+        .standard_filters(!options.no_ignore)
+        // Dotfiles were always scanned; only ignore rules and DEFAULT_SKIP prune
+        .hidden(false)
+        // Honour .gitignore even when the root is not a git checkout
+        .require_git(false)
+        .overrides(overrides.build()?)
+        .build();
+    Ok(walk)
+}
+
 fn is_js_ts_file(path: &Path, typescript: bool) -> bool {
     if let Some(ext) = path.extension() {
         let ext = ext.to_string_lossy();
@@ -127,49 +336,176 @@ fn is_js_ts_file(path: &Path, typescript: bool) -> bool {
     }
 }
 
-fn scan_file(cm: &Lrc<SourceMap>, path: &Path) -> Result<Vec<GenManifest>> {
+fn scan_file(
+    cm: &Lrc<SourceMap>,
+    path: &Path,
+    options: &ScanOptions,
+    graph: &mut CallGraph,
+    modules: &mut Vec<ModuleRecord>,
+) -> Result<Vec<GenManifest>> {
     let content = std::fs::read_to_string(path)?;
+    if let Some(markers) = &options.generated_markers {
+        if generated::is_generated(&content, markers) {
+            info!("Skipping generated file {:?}", path);
+            return Ok(Vec::new());
+        }
+    }
+    
+    if !markdown::is_markdown(path) {
+        let unit = SourceUnit {
+            path,
+            name: path.to_string_lossy().to_string(),
+            source: content,
+            syntax: options.parser.syntax_for(path),
+            first_line: 1,
+        };
+        return scan_unit(cm, &unit, options, graph, modules);
+    }
+    
+    // Each fence is a unit of its own; one that does not parse is skipped
+    let mut genes = Vec::new();
+    for block in markdown::code_blocks(&content) {
+        let unit = SourceUnit {
+            path,
+            name: format!("{}#block-{}", path.to_string_lossy(), block.index),
+            syntax: options.parser.syntax(block.is_jsx()),
+            first_line: block.line,
+            source: block.source,
+        };
+        match scan_unit(cm, &unit, options, graph, modules) {
+            Ok(mut block_genes) => genes.append(&mut block_genes),
+            Err(e) if options.fail_fast => return Err(e.context(format!("In {}", unit.name))),
+            Err(e) => warn!("Failed to scan {}: {:#}", unit.name, e),
+        }
+    }
+    Ok(genes)
+}
+
+/// Source parsed as one module: a whole file or a Markdown code fence
+struct SourceUnit<'a> {
+    path: &'a Path,
+    /// Prefix of gene ids, e.g. `docs/a.md#block-2`
+    name: String,
+    source: String,
+    syntax: Syntax,
+    /// Line of `source`'s first line within the file
+    first_line: usize,
+}
+
+fn scan_unit(
+    cm: &Lrc<SourceMap>,
+    unit: &SourceUnit,
+    options: &ScanOptions,
+    graph: &mut CallGraph,
+    modules: &mut Vec<ModuleRecord>,
+) -> Result<Vec<GenManifest>> {
+    let content = &unit.source;
     let fm = cm.new_source_file(
-        swc_common::FileName::Real(path.to_path_buf()),
+        swc_common::FileName::Custom(unit.name.clone()),
         content.clone(),
     );
     
     let lexer = Lexer::new(
-        Syntax::Typescript(Default::default()),
-        Default::default(),
+        unit.syntax,
+        options.parser.target,
         StringInput::from(&*fm),
         None,
     );
     
     let mut parser = SwcParser::new_from(lexer);
-    let module = parser.parse_module()?;
+    let module = parser
+        .parse_module()
+        .map_err(|e| parse_error(cm, unit, &e))?;
     
-    let mut checker = PurityChecker::new();
-    let functions = checker.extract_pure_functions(&module);
+    let mut checker = PurityChecker::new()
+        .with_heuristic(options.heuristic)
+        .with_config(options.config.clone());
+    let functions = checker.analyze(&module);
     
     let mut genes = Vec::new();
+    let mut seen: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
     
-    for func in functions {
-        let body = content[func.span.lo.0 as usize..func.span.hi.0 as usize].to_string();
+    // Same scope path twice in one file (e.g. two arrows in one initializer)
+    let functions: Vec<(String, purity::PureFunction)> = functions
+        .into_iter()
+        .map(|func| {
+            let qualified = func.qualified_name();
+            let occurrence = seen.entry(qualified.clone()).or_insert(0);
+            *occurrence += 1;
+            let id = match *occurrence {
+                1 => format!("{}#{}", unit.name, qualified),
+                n => format!("{}#{}~{}", unit.name, qualified, n),
+            };
+            (id, func)
+        })
+        .collect();
+    graph.add_file(&functions);
+    
+    let mut leaves = Vec::new();
+    for (id, func) in functions {
+        let ast_hash = normalize::ast_hash(&module, func.span);
+        leaves.extend(ast_hash.clone());
+        if !func.is_pure {
+            debug!("Skipping impure {} ({})", func.name, func.impure_reasons.join(", "));
+            continue;
+        }
+        if options.exports_only && !func.exported {
+            debug!("Skipping internal {}", func.name);
+            continue;
+        }
+        
+        // Spans are global to the SourceMap; rebase them onto this file.
+        // They index `fm.src`, which has any BOM stripped, not `content`
+        let lo = (func.span.lo - fm.start_pos).0 as usize;
+        let hi = (func.span.hi - fm.start_pos).0 as usize;
+        let body = fm.src[lo..hi].to_string();
         let hash = compute_content_hash(&body);
-        let ast_hash = compute_ast_hash(&func);
+        let ast_hash = ast_hash.ok_or_else(|| anyhow::anyhow!("No AST found for {}", func.name))?;
+        let normalized_body_hash = if options.hash_bodies_normalized {
+            normalize::normalized_body_hash(&module, func.span, options.rename_strategy)
+        } else {
+            None
+        };
         
         genes.push(GenManifest {
             name: func.name.clone(),
+            id,
             hash,
             ast_hash,
-            path: path.to_string_lossy().to_string(),
-            line: cm.lookup_line(func.span.lo).unwrap_or(0) as u32 + 1,
+            frontend_version: encode::FRONTEND_VERSION.to_string(),
+            path: unit.path.to_string_lossy().to_string(),
+            line: (cm.lookup_char_pos(func.span.lo).line + unit.first_line - 1) as u32,
             pure: true,
             params: func.params.clone(),
+            param_types: func.param_types.clone(),
             return_type: func.return_type.clone(),
             body,
+            normalized_body_hash,
         });
     }
     
+    modules.push(ModuleRecord {
+        unit: unit.name.clone(),
+        path: unit.path.to_string_lossy().to_string(),
+        frontend_version: encode::FRONTEND_VERSION.to_string(),
+        hash: merkle::module_hash(&leaves),
+    });
+    
     Ok(genes)
 }
 
+/// `path:line:col: message`, so a failure in a large scan can be located
+fn parse_error(cm: &SourceMap, unit: &SourceUnit, error: &swc_ecma_parser::error::Error) -> anyhow::Error {
+    let loc = cm.lookup_char_pos(error.span().lo);
+    anyhow::anyhow!(
+        "{}:{}:{}: Parse error: {}",
+        unit.path.display(),
+        loc.line + unit.first_line - 1,
+        loc.col_display + 1,
+        error.kind().msg()
+    )
+}
+
 fn compute_content_hash(content: &str) -> String {
     use sha2::{Sha256, Digest};
     let mut hasher = Sha256::new();
@@ -178,24 +514,6 @@ fn compute_content_hash(content: &str) -> String {
     format!("sha256:{}", hex::encode(result))
 }
 
-fn compute_ast_hash(func: &purity::PureFunction) -> String {
-    // Simplified canonical AST hash
-    // In real implementation, normalize AST structure
-    use sha2::{Sha256, Digest};
-    let mut hasher = Sha256::new();
-    
-    hasher.update(func.name.as_bytes());
-    for param in &func.params {
-        hasher.update(param.as_bytes());
-    }
-    if let Some(rt) = &func.return_type {
-        hasher.update(rt.as_bytes());
-    }
-    
-    let result = hasher.finalize();
-    format!("canonical:{}", hex::encode(result))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,4 +524,284 @@ mod tests {
         let hash = compute_content_hash(content);
         assert!(hash.starts_with("sha256:"));
     }
+    
+    fn test_options() -> ScanOptions {
+        ScanOptions {
+            typescript: true,
+            markdown: false,
+            max_depth: 10,
+            no_ignore: false,
+            exclude: Vec::new(),
+            generated_markers: None,
+            hash_bodies_normalized: false,
+            rename_strategy: RenameStrategy::Full,
+            heuristic: false,
+            exports_only: false,
+            config: PurityConfig::default(),
+            parser: ParserOptions::default(),
+            call_graph: None,
+            module_hashes: None,
+            fail_fast: false,
+        }
+    }
+    
+    fn scan_source(code: &str) -> Vec<GenManifest> {
+        scan_source_with(code, &test_options())
+    }
+    
+    fn scan_source_with(code: &str, options: &ScanOptions) -> Vec<GenManifest> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("genes.ts");
+        std::fs::write(&path, code).unwrap();
+        
+        scan_file(&Default::default(), &path, options, &mut CallGraph::default(), &mut Vec::new()).unwrap()
+    }
+    
+    #[test]
+    fn test_walk_skips_ignored_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for file in [
+            "src/a.js",
+            "src/a.test.js",
+            "node_modules/dep/index.js",
+            "dist/bundle.js",
+            "generated/out.js",
+            ".eslintrc.js",
+            ".config/tool.js",
+        ] {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        std::fs::write(root.join(".gitignore"), "generated/\n").unwrap();
+        
+        let walked = |options: &ScanOptions| {
+            let mut files: Vec<String> = walker(root, options)
+                .unwrap()
+                .filter_map(|e| e.ok())
+                .filter(|e| is_js_ts_file(e.path(), true))
+                .map(|e| e.path().strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/"))
+                .collect();
+            files.sort();
+            files
+        };
+        
+        let options = ScanOptions {
+            exclude: vec!["*.test.js".to_string()],
+            ..test_options()
+        };
+        assert_eq!(walked(&options), vec![".config/tool.js", ".eslintrc.js", "src/a.js"]);
+        
+        let everything = ScanOptions {
+            no_ignore: true,
+            ..test_options()
+        };
+        assert_eq!(walked(&everything).len(), 7);
+        
+        let shallow = ScanOptions {
+            max_depth: 1,
+            ..test_options()
+        };
+        assert_eq!(walked(&shallow), vec![".eslintrc.js"]);
+    }
+    
+    #[test]
+    fn test_exclude_generated() {
+        let code = "// @generated by protoc-gen-ts\nexport function id(x) { return x; }\n";
+        let options = ScanOptions {
+            generated_markers: Some(vec!["@generated".to_string()]),
+            ..test_options()
+        };
+        
+        assert_eq!(scan_source(code).len(), 1);
+        assert!(scan_source_with(code, &options).is_empty());
+    }
+    
+    #[test]
+    fn test_exports_only() {
+        let code = r#"
+            function helper(x) {
+                return x * 2;
+            }
+            
+            export function double(x) {
+                return x * 2;
+            }
+        "#;
+        
+        let options = ScanOptions {
+            exports_only: true,
+            ..test_options()
+        };
+        let genes = scan_source_with(code, &options);
+        
+        assert_eq!(scan_source(code).len(), 2);
+        assert_eq!(genes.len(), 1);
+        assert_eq!(genes[0].name, "double");
+    }
+    
+    #[test]
+    fn test_bom_does_not_shift_bodies() {
+        let code = "function neg(x) {\n    return -x; //€\n}\n";
+        let with_bom = scan_source(&format!("\u{feff}{}", code));
+        let without = scan_source(code);
+        
+        assert_eq!(with_bom[0].body, "function neg(x) {\n    return -x; //€\n}");
+        assert_eq!(with_bom[0].hash, without[0].hash);
+    }
+    
+    #[test]
+    fn test_rename_strategy_requires_normalized_hashes() {
+        let scan = ["virus-deconstructor", "scan", "--root", "src", "--out", "genes.ndjson"];
+        
+        assert!(Cli::try_parse_from(scan).is_ok());
+        assert!(Cli::try_parse_from(scan.iter().chain(&["--rename-strategy", "hashed"])).is_err());
+        assert!(Cli::try_parse_from(
+            scan.iter().chain(&["--rename-strategy", "hashed", "--hash-bodies-normalized"])
+        )
+        .is_ok());
+    }
+    
+    #[test]
+    fn test_parse_error_has_position() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.ts");
+        std::fs::write(&path, "function ok(a) {\n    return a;\n}\nconst = 1;\n").unwrap();
+        
+        let err = scan_file(&Default::default(), &path, &test_options(), &mut CallGraph::default(), &mut Vec::new())
+            .unwrap_err()
+            .to_string();
+        
+        let expected = format!("{}:4:7: Parse error", path.display());
+        assert!(err.starts_with(&expected), "{}", err);
+    }
+    
+    #[test]
+    fn test_fail_fast_aborts_on_bad_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("src");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(root.join("a.ts"), "function add(a, b) { return a + b; }").unwrap();
+        std::fs::write(root.join("broken.ts"), "const = 1;").unwrap();
+        std::fs::write(root.join("c.ts"), "function neg(a) { return -a; }").unwrap();
+        let out = dir.path().join("genes.ndjson");
+        
+        scan_directory(&root, &out, &test_options()).unwrap();
+        assert_eq!(std::fs::read_to_string(&out).unwrap().lines().count(), 2);
+        std::fs::remove_file(&out).unwrap();
+        
+        let options = ScanOptions {
+            fail_fast: true,
+            ..test_options()
+        };
+        let err = scan_directory(&root, &out, &options).unwrap_err();
+        assert!(format!("{:#}", err).contains("broken.ts:1:7: Parse error"), "{:#}", err);
+        assert!(!out.exists());
+    }
+    
+    #[test]
+    fn test_module_hashes_written_per_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("src");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(
+            root.join("a.ts"),
+            "function add(a, b) { return a + b; }\nfunction log(x) { console.log(x); }\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("b.ts"), "function neg(a) { return -a; }").unwrap();
+        let out = dir.path().join("genes.ndjson");
+        let hashes = dir.path().join("modules.ndjson");
+        
+        let options = ScanOptions {
+            module_hashes: Some(hashes.clone()),
+            ..test_options()
+        };
+        scan_directory(&root, &out, &options).unwrap();
+        
+        let records: Vec<serde_json::Value> = std::fs::read_to_string(&hashes)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let genes: Vec<GenManifest> = std::fs::read_to_string(&out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let a = records.iter().find(|r| r["unit"].as_str().unwrap().ends_with("a.ts")).unwrap();
+        
+        assert_eq!(records.len(), 2);
+        assert!(a["root"].as_str().unwrap().starts_with("merkle:"));
+        // The impure `log` is a leaf too, though it is not a gene
+        let leaves = a["leaves"].as_array().unwrap();
+        assert_eq!(leaves.len(), 2);
+        let add = genes.iter().find(|g| g.name == "add").unwrap();
+        assert!(leaves.contains(&serde_json::json!(add.ast_hash)));
+    }
+    
+    #[test]
+    fn test_markdown_fences_scanned_separately() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("guide.md");
+        std::fs::write(
+            &path,
+            "# Guide\n\n```ts\nfunction add(a: number, b: number) {\n    return a + b;\n}\n```\n\n\
+             ```ts\nconst = broken;\n```\n\n```tsx\nconst sum = (x, y) => x + y;\n```\n",
+        )
+        .unwrap();
+        
+        let genes = scan_file(&Default::default(), &path, &test_options(), &mut CallGraph::default(), &mut Vec::new()).unwrap();
+        let name = path.to_string_lossy();
+        
+        assert_eq!(genes.len(), 2);
+        assert_eq!(genes[0].id, format!("{}#block-1#add", name));
+        assert_eq!(genes[0].line, 4);
+        assert_eq!(genes[1].id, format!("{}#block-3#sum", name));
+        assert_eq!(genes[1].line, 14);
+        assert_eq!(genes[0].path, name);
+        assert_ne!(genes[0].hash, genes[1].hash);
+    }
+    
+    #[test]
+    fn test_ast_hash_ignores_names() {
+        let genes = scan_source(
+            r#"
+            function add(a, b) { return a + b; }
+            function sum(x, y) { return x + y; }
+            function diff(a, b) { return a - b; }
+        "#,
+        );
+        
+        assert_ne!(genes[0].hash, genes[1].hash);
+        assert_eq!(genes[0].ast_hash, genes[1].ast_hash);
+        assert_ne!(genes[0].ast_hash, genes[2].ast_hash);
+    }
+    
+    #[test]
+    fn test_ast_hash_ignores_offset() {
+        let code = "function add(a, b) { return a + b; }";
+        let shifted = format!("\n\n    // moved\n{}", code);
+        assert_eq!(scan_source(code)[0].ast_hash, scan_source(&shifted)[0].ast_hash);
+    }
+    
+    #[test]
+    fn test_identical_arrows_distinct_ids() {
+        let genes = scan_source(
+            r#"
+            const doubled = [1, 2].map(x => x * 2);
+            const twice = [3, 4].map(x => x * 2);
+            const both = [[x => x + 1], [x => x + 1]];
+        "#,
+        );
+        
+        assert_eq!(genes.len(), 4);
+        assert!(genes[0].id.ends_with("#doubled/arrow"));
+        assert!(genes[1].id.ends_with("#twice/arrow"));
+        assert!(genes[2].id.ends_with("#both/arrow"));
+        assert!(genes[3].id.ends_with("#both/arrow~2"));
+        assert_eq!(genes[0].hash, genes[1].hash);
+        assert_eq!(genes[2].hash, genes[3].hash);
+    }
 }
\ No newline at end of file
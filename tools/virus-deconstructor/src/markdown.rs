@@ -0,0 +1,103 @@
+use std::path::Path;
+
+/// Fence info strings scanned as code, e.g. ```` ```ts ````
+const CODE_LANGS: &[&str] = &["ts", "tsx", "typescript", "js", "jsx", "javascript"];
+
+/// A fenced JS/TS block in a Markdown document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBlock {
+    /// 1-based among the document's ts/js fences only; other fences (e.g.
+    /// `bash`) are not counted. As in `README.md#block-2`
+    pub index: usize,
+    pub lang: String,
+    /// 1-based line of the first code line in the document
+    pub line: usize,
+    pub source: String,
+}
+
+impl CodeBlock {
+    /// JSX is enabled by the fence itself, not by tsconfig
+    pub fn is_jsx(&self) -> bool {
+        matches!(self.lang.as_str(), "tsx" | "jsx")
+    }
+}
+
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "md" || ext == "mdx")
+}
+
+/// Backtick and tilde fences of three or more; an unclosed fence runs to
+/// the end of the document, as CommonMark has it.
+pub fn code_blocks(content: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut open: Option<(String, usize, String)> = None;
+    let mut source = String::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        match &open {
+            Some((fence, start, lang)) => {
+                if trimmed.starts_with(fence.as_str()) && trimmed.trim_end().chars().all(|c| fence.starts_with(c)) {
+                    if CODE_LANGS.contains(&lang.as_str()) {
+                        blocks.push(CodeBlock {
+                            index: blocks.len() + 1,
+                            lang: lang.clone(),
+                            line: *start,
+                            source: std::mem::take(&mut source),
+                        });
+                    }
+                    source.clear();
+                    open = None;
+                } else {
+                    source.push_str(line);
+                    source.push('\n');
+                }
+            }
+            None => {
+                if let Some(fence) = fence_of(trimmed) {
+                    let lang = trimmed[fence.len()..].split_whitespace().next().unwrap_or("");
+                    open = Some((fence, i + 2, lang.to_ascii_lowercase()));
+                }
+            }
+        }
+    }
+
+    if let Some((_, start, lang)) = open {
+        if CODE_LANGS.contains(&lang.as_str()) {
+            blocks.push(CodeBlock {
+                index: blocks.len() + 1,
+                lang,
+                line: start,
+                source,
+            });
+        }
+    }
+
+    blocks
+}
+
+fn fence_of(line: &str) -> Option<String> {
+    let marker = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = line.chars().take_while(|c| *c == marker).count();
+    (len >= 3).then(|| marker.to_string().repeat(len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_blocks() {
+        let doc = "# Utils\n\n```ts\nconst a = 1;\n```\n\n```bash\nls\n```\n\n~~~~tsx title=\"view\"\nconst v = <b/>;\n```\n~~~~\n";
+        let blocks = code_blocks(doc);
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!((blocks[0].index, blocks[0].lang.as_str(), blocks[0].line), (1, "ts", 4));
+        assert_eq!(blocks[0].source, "const a = 1;\n");
+
+        // A shorter fence inside a longer one is content
+        assert_eq!((blocks[1].index, blocks[1].lang.as_str(), blocks[1].line), (2, "tsx", 12));
+        assert_eq!(blocks[1].source, "const v = <b/>;\n```\n");
+        assert!(blocks[1].is_jsx());
+    }
+}
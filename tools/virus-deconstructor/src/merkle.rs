@@ -0,0 +1,82 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Module fingerprint built from per-function hashes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModuleHash {
+    pub root: String,
+    /// Per-function `ast_hash`es in sorted (leaf) order
+    pub leaves: Vec<String>,
+}
+
+/// Merkle root over the sorted per-function `ast_hash`es.
+///
+/// Sorting makes the root independent of declaration order, and an edit
+/// needs only the stored leaves plus the changed function's new hash to
+/// recompute. Leaves are paired by sorted position, though, so a new hash
+/// that sorts elsewhere re-pairs every leaf between its old and new place.
+pub fn module_hash<S: AsRef<str>>(ast_hashes: &[S]) -> ModuleHash {
+    let mut leaves: Vec<String> = ast_hashes.iter().map(|h| h.as_ref().to_string()).collect();
+    leaves.sort();
+
+    let mut level: Vec<[u8; 32]> = leaves.iter().map(|l| hash_leaf(l)).collect();
+    if level.is_empty() {
+        level.push(Sha256::digest([]).into());
+    }
+
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_node(left, right),
+                // Odd node is promoted unchanged
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    ModuleHash {
+        root: format!("merkle:{}", hex::encode(level[0])),
+        leaves,
+    }
+}
+
+// Domain-separate leaves from inner nodes so a leaf can't pose as a subtree
+fn hash_leaf(leaf: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0u8]);
+    hasher.update(leaf.as_bytes());
+    hasher.finalize().into()
+}
+
+fn hash_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([1u8]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_ignores_function_order() {
+        let a = module_hash(&["canonical:aa", "canonical:bb", "canonical:cc"]);
+        let b = module_hash(&["canonical:cc", "canonical:aa", "canonical:bb"]);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_changing_one_function_changes_root_only() {
+        let before = module_hash(&["canonical:aa", "canonical:bb", "canonical:cc"]);
+        let after = module_hash(&["canonical:aa", "canonical:bx", "canonical:cc"]);
+
+        assert_ne!(before.root, after.root);
+        assert!(after.leaves.contains(&"canonical:aa".to_string()));
+        assert!(after.leaves.contains(&"canonical:cc".to_string()));
+        assert!(!after.leaves.contains(&"canonical:bb".to_string()));
+    }
+}
@@ -0,0 +1,544 @@
+use crate::encode;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use swc_common::{Span, DUMMY_SP};
+use swc_ecma_ast::*;
+use swc_ecma_visit::{Visit, VisitMut, VisitMutWith, VisitWith};
+
+/// How bound identifiers appear in the canonical form. Renamed names start
+/// with `#`, which no identifier can contain, so they never collide with a
+/// free identifier such as a global `v0`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenameStrategy {
+    /// `#N` in first-encounter order; names are erased entirely
+    #[default]
+    Full,
+    /// A stable hash of the original name, so `getUser` and `getOrder` differ
+    Hashed,
+    /// Original names
+    None,
+}
+
+impl std::str::FromStr for RenameStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(Self::Full),
+            "hashed" => Ok(Self::Hashed),
+            "none" => Ok(Self::None),
+            _ => Err(format!("unknown rename strategy {:?} (expected full, hashed or none)", s)),
+        }
+    }
+}
+
+/// SHA-256 of the function's canonical form, prefixed `normalized:`
+pub fn normalized_body_hash(module: &Module, span: Span, strategy: RenameStrategy) -> Option<String> {
+    let canonical = canonical_form(module, span, strategy)?;
+    Some(format!("normalized:{}", sha256_hex(&canonical)))
+}
+
+/// SHA-256 of the alpha-renamed function, prefixed `canonical:`.
+///
+/// Names, layout and types are ignored as in `normalized_body_hash`, but
+/// operand and key order still count, so `a * b` and `b * a` differ.
+pub fn ast_hash(module: &Module, span: Span) -> Option<String> {
+    let canonical = render(module, span, RenameStrategy::Full, false)?;
+    Some(format!("canonical:{}", sha256_hex(&canonical)))
+}
+
+fn sha256_hex(canonical: &str) -> String {
+    hex::encode(Sha256::digest(canonical.as_bytes()))
+}
+
+/// Canonical form of the function whose span is `span`.
+///
+/// Bound identifiers (the function's own name, params, locals) are renamed
+/// per `strategy`, operands of commutative operators and object literal keys
+/// are sorted, and the result is written in the stable encoding from
+/// `encode`, which drops spans and type annotations. Free identifiers and
+/// property names are kept, so `Math.max` and `Math.min` stay distinct.
+pub fn canonical_form(module: &Module, span: Span, strategy: RenameStrategy) -> Option<String> {
+    render(module, span, strategy, true)
+}
+
+fn render(module: &Module, span: Span, strategy: RenameStrategy, sort_commutative: bool) -> Option<String> {
+    let mut finder = FunctionFinder {
+        span,
+        self_name: None,
+        found: None,
+    };
+    module.visit_with(&mut finder);
+    let found = finder.found?;
+
+    let mut bindings = BindingCollector::default();
+    if let Some(name) = &finder.self_name {
+        bindings.names.insert(name.clone());
+    }
+
+    let renamer = |bindings: BindingCollector| Renamer {
+        bound: bindings.names,
+        renamed: HashMap::new(),
+        strategy,
+        sort_commutative,
+    };
+
+    let canonical = match found {
+        Found::Function(mut function) => {
+            function.visit_with(&mut bindings);
+            function.visit_mut_with(&mut renamer(bindings));
+            format!("fn:{}", encode::encode_function(&function))
+        }
+        Found::Arrow(mut arrow) => {
+            arrow.visit_with(&mut bindings);
+            arrow.visit_mut_with(&mut renamer(bindings));
+            format!("arrow:{}", encode::encode_arrow(&arrow))
+        }
+    };
+
+    Some(canonical)
+}
+
+enum Found {
+    Function(Box<Function>),
+    Arrow(Box<ArrowExpr>),
+}
+
+struct FunctionFinder {
+    span: Span,
+    self_name: Option<String>,
+    found: Option<Found>,
+}
+
+impl Visit for FunctionFinder {
+    fn visit_fn_decl(&mut self, node: &FnDecl) {
+        if node.function.span == self.span {
+            self.self_name = Some(node.ident.sym.to_string());
+        }
+        node.visit_children_with(self);
+    }
+
+    fn visit_fn_expr(&mut self, node: &FnExpr) {
+        if node.function.span == self.span {
+            self.self_name = node.ident.as_ref().map(|i| i.sym.to_string());
+        }
+        node.visit_children_with(self);
+    }
+
+    fn visit_function(&mut self, node: &Function) {
+        if self.found.is_none() && node.span == self.span {
+            self.found = Some(Found::Function(Box::new(node.clone())));
+            return;
+        }
+        node.visit_children_with(self);
+    }
+
+    fn visit_arrow_expr(&mut self, node: &ArrowExpr) {
+        if self.found.is_none() && node.span == self.span {
+            self.found = Some(Found::Arrow(Box::new(node.clone())));
+            return;
+        }
+        node.visit_children_with(self);
+    }
+}
+
+/// Names introduced inside the function: params, locals, nested declarations
+#[derive(Default)]
+struct BindingCollector {
+    names: HashSet<String>,
+}
+
+impl Visit for BindingCollector {
+    fn visit_binding_ident(&mut self, node: &BindingIdent) {
+        self.names.insert(node.id.sym.to_string());
+    }
+
+    fn visit_fn_decl(&mut self, node: &FnDecl) {
+        self.names.insert(node.ident.sym.to_string());
+        node.visit_children_with(self);
+    }
+
+    fn visit_fn_expr(&mut self, node: &FnExpr) {
+        if let Some(ident) = &node.ident {
+            self.names.insert(ident.sym.to_string());
+        }
+        node.visit_children_with(self);
+    }
+
+    fn visit_class_decl(&mut self, node: &ClassDecl) {
+        self.names.insert(node.ident.sym.to_string());
+        node.visit_children_with(self);
+    }
+}
+
+struct Renamer {
+    bound: HashSet<String>,
+    renamed: HashMap<String, String>,
+    strategy: RenameStrategy,
+    sort_commutative: bool,
+}
+
+impl Renamer {
+    fn canonical_name(&mut self, name: &str) -> Option<String> {
+        if !self.bound.contains(name) {
+            return None;
+        }
+        match self.strategy {
+            RenameStrategy::Full => {}
+            RenameStrategy::Hashed => {
+                let digest = hex::encode(Sha256::digest(name.as_bytes()));
+                return Some(format!("#h{}", &digest[..16]));
+            }
+            RenameStrategy::None => return None,
+        }
+        let next = self.renamed.len();
+        Some(
+            self.renamed
+                .entry(name.to_string())
+                .or_insert_with(|| format!("#{}", next))
+                .clone(),
+        )
+    }
+}
+
+impl VisitMut for Renamer {
+    fn visit_mut_span(&mut self, span: &mut Span) {
+        *span = DUMMY_SP;
+    }
+
+    fn visit_mut_ident(&mut self, node: &mut Ident) {
+        node.span = DUMMY_SP;
+        if let Some(name) = self.canonical_name(&node.sym) {
+            node.sym = name.into();
+        }
+    }
+
+    // Property names are structure, not bindings: drop only their spans
+    fn visit_mut_member_prop(&mut self, node: &mut MemberProp) {
+        match node {
+            MemberProp::Computed(computed) => computed.visit_mut_with(self),
+            _ => node.visit_mut_with(&mut SpanEraser),
+        }
+    }
+
+    fn visit_mut_prop_name(&mut self, node: &mut PropName) {
+        match node {
+            PropName::Computed(computed) => computed.visit_mut_with(self),
+            _ => node.visit_mut_with(&mut SpanEraser),
+        }
+    }
+
+    // `{ a }` keeps its key when `a` is renamed
+    fn visit_mut_prop(&mut self, node: &mut Prop) {
+        if let Prop::Shorthand(ident) = node {
+            let key = Ident::new(ident.sym.clone(), DUMMY_SP);
+            let mut value = ident.clone();
+            value.visit_mut_with(self);
+            *node = Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(key),
+                value: Box::new(Expr::Ident(value)),
+            });
+            return;
+        }
+        node.visit_mut_children_with(self);
+    }
+
+    // `const { a } = o` keeps reading `a` when the binding is renamed
+    fn visit_mut_object_pat_prop(&mut self, node: &mut ObjectPatProp) {
+        if let ObjectPatProp::Assign(prop) = node {
+            let key = Ident::new(prop.key.id.sym.clone(), DUMMY_SP);
+            let mut value = Pat::Ident(prop.key.clone());
+            if let Some(default) = prop.value.take() {
+                value = Pat::Assign(AssignPat {
+                    span: DUMMY_SP,
+                    left: Box::new(value),
+                    right: default,
+                });
+            }
+            value.visit_mut_with(self);
+            *node = ObjectPatProp::KeyValue(KeyValuePatProp {
+                key: PropName::Ident(key),
+                value: Box::new(value),
+            });
+            return;
+        }
+        node.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_binding_ident(&mut self, node: &mut BindingIdent) {
+        node.type_ann = None;
+        node.id.visit_mut_with(self);
+    }
+
+    fn visit_mut_function(&mut self, node: &mut Function) {
+        node.type_params = None;
+        node.return_type = None;
+        node.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_arrow_expr(&mut self, node: &mut ArrowExpr) {
+        node.type_params = None;
+        node.return_type = None;
+        node.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_object_lit(&mut self, node: &mut ObjectLit) {
+        node.visit_mut_children_with(self);
+
+        // Spreads and computed keys make order observable
+        if self.sort_commutative {
+            let keys: Option<Vec<String>> = node.props.iter().map(static_key).collect();
+            if let Some(keys) = keys {
+                let mut props: Vec<(String, PropOrSpread)> = keys.into_iter().zip(node.props.drain(..)).collect();
+                props.sort_by(|a, b| a.0.cmp(&b.0));
+                node.props = props.into_iter().map(|(_, prop)| prop).collect();
+            }
+        }
+    }
+
+    fn visit_mut_bin_expr(&mut self, node: &mut BinExpr) {
+        node.visit_mut_children_with(self);
+
+        if self.sort_commutative
+            && is_commutative(node)
+            && encode::encode_expr(&node.left) > encode::encode_expr(&node.right)
+        {
+            std::mem::swap(&mut node.left, &mut node.right);
+        }
+    }
+}
+
+struct SpanEraser;
+
+impl VisitMut for SpanEraser {
+    fn visit_mut_span(&mut self, span: &mut Span) {
+        *span = DUMMY_SP;
+    }
+}
+
+fn static_key(prop: &PropOrSpread) -> Option<String> {
+    let PropOrSpread::Prop(prop) = prop else {
+        return None;
+    };
+    let key = match &**prop {
+        Prop::Shorthand(ident) => return Some(ident.sym.to_string()),
+        Prop::KeyValue(p) => &p.key,
+        Prop::Getter(p) => &p.key,
+        Prop::Setter(p) => &p.key,
+        Prop::Method(p) => &p.key,
+        Prop::Assign(_) => return None,
+    };
+    match key {
+        PropName::Ident(ident) => Some(ident.sym.to_string()),
+        PropName::Str(s) => Some(s.value.to_string()),
+        PropName::Num(n) => Some(n.value.to_string()),
+        PropName::BigInt(b) => Some(b.value.to_string()),
+        PropName::Computed(_) => None,
+    }
+}
+
+fn is_commutative(node: &BinExpr) -> bool {
+    match node.op {
+        BinaryOp::Mul
+        | BinaryOp::BitAnd
+        | BinaryOp::BitOr
+        | BinaryOp::BitXor
+        | BinaryOp::EqEq
+        | BinaryOp::NotEq
+        | BinaryOp::EqEqEq
+        | BinaryOp::NotEqEq => true,
+        // `+` concatenates unless both sides are known to be numbers
+        BinaryOp::Add => is_numeric(&node.left) && is_numeric(&node.right),
+        _ => false,
+    }
+}
+
+/// Evaluates to a number or BigInt whatever its operands are
+fn is_numeric(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(Lit::Num(_) | Lit::BigInt(_)) | Expr::Update(_) => true,
+        Expr::Paren(paren) => is_numeric(&paren.expr),
+        Expr::Unary(unary) => matches!(unary.op, UnaryOp::Minus | UnaryOp::Plus | UnaryOp::Tilde),
+        Expr::Bin(bin) => match bin.op {
+            BinaryOp::Add => is_numeric(&bin.left) && is_numeric(&bin.right),
+            BinaryOp::Sub
+            | BinaryOp::Mul
+            | BinaryOp::Div
+            | BinaryOp::Mod
+            | BinaryOp::Exp
+            | BinaryOp::BitAnd
+            | BinaryOp::BitOr
+            | BinaryOp::BitXor
+            | BinaryOp::LShift
+            | BinaryOp::RShift
+            | BinaryOp::ZeroFillRShift => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::purity::PurityChecker;
+    use crate::test_support::parse_module;
+
+    fn hashes(code: &str) -> Vec<String> {
+        hashes_with(code, RenameStrategy::Full)
+    }
+
+    fn hashes_with(code: &str, strategy: RenameStrategy) -> Vec<String> {
+        let module = parse_module(code);
+        PurityChecker::new()
+            .analyze(&module)
+            .iter()
+            .map(|f| normalized_body_hash(&module, f.span, strategy).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_renamed_and_reformatted_copies_match() {
+        let original = hashes("function scale(value, factor) { return value * factor; }");
+        let copy = hashes(
+            r#"
+            function mul(
+                x: number,
+                y: number
+            ): number {
+                return y * x;
+            }
+        "#,
+        );
+        assert_eq!(original, copy);
+    }
+
+    #[test]
+    fn test_different_logic_differs() {
+        let sub = hashes("function f(a, b) { return a - b; }");
+        let rsub = hashes("function f(a, b) { return b - a; }");
+        let concat = hashes("function f(a) { return a + 'x'; }");
+        let rconcat = hashes("function f(a) { return 'x' + a; }");
+        assert_ne!(sub, rsub);
+        assert_ne!(concat, rconcat);
+
+        // Either side may be a string, so `+` keeps its order
+        let add = hashes("function f(a, b) { return a + b; }");
+        let radd = hashes("function f(a, b) { return b + a; }");
+        assert_ne!(add, radd);
+    }
+
+    #[test]
+    fn test_numeric_addition_sorted() {
+        let sum = hashes("function f(a) { return a * 2 + 1; }");
+        let rsum = hashes("function f(a) { return 1 + a * 2; }");
+        assert_eq!(sum, rsum);
+    }
+
+    #[test]
+    fn test_free_identifiers_kept() {
+        let max = hashes("function f(a, b) { return Math.max(a, b); }");
+        let min = hashes("function f(a, b) { return Math.min(a, b); }");
+        assert_ne!(max, min);
+    }
+
+    #[test]
+    fn test_loops_alpha_renamed() {
+        let for_i = hashes(
+            "function total(xs) { let t = 0; for (let i = 0; i < xs.length; i++) { t += xs[i]; } return t; }",
+        );
+        let for_j = hashes(
+            "function total(ys) { let s = 0; for (let j = 0; j < ys.length; j++) { s += ys[j]; } return s; }",
+        );
+        assert_eq!(for_i, for_j);
+
+        let loops = [
+            ("function f(o) { for (const k in o) { o[k]; } }", "function f(p) { for (const q in p) { p[q]; } }"),
+            ("function f(xs) { for (const x of xs) { x; } }", "function f(ys) { for (const y of ys) { y; } }"),
+            ("function f(n) { while (n > 0) { n--; } }", "function f(m) { while (m > 0) { m--; } }"),
+            ("function f(n) { do { n--; } while (n > 0); }", "function f(m) { do { m--; } while (m > 0); }"),
+        ];
+        for (code, renamed) in loops {
+            assert_eq!(hashes(code), hashes(renamed), "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_object_key_order_ignored() {
+        let ab = hashes("function f(x) { return { a: x, b: 2 }; }");
+        let ba = hashes("function f(y) { return { b: 2, a: y }; }");
+        assert_eq!(ab, ba);
+
+        // Later spreads override earlier keys, so their order is kept
+        let spread_last = hashes("function f(x, o) { return { a: x, ...o }; }");
+        let spread_first = hashes("function f(x, o) { return { ...o, a: x }; }");
+        assert_ne!(spread_last, spread_first);
+
+        let two = hashes("function f(a, b) { return [a, b]; }");
+        let three = hashes("function f(a, b) { return [a, b, b]; }");
+        assert_ne!(two, three);
+    }
+
+    #[test]
+    fn test_destructuring_keeps_property_names() {
+        let shorthand = hashes("function f(o) { const { a, b = 1 } = o; return a + b; }");
+        let explicit = hashes("function f(p) { const { a: x, b: y = 1 } = p; return x + y; }");
+        let other_key = hashes("function f(o) { const { c, b = 1 } = o; return c + b; }");
+        assert_eq!(shorthand, explicit);
+        assert_ne!(shorthand, other_key);
+
+        let array = hashes("function f(xs) { const [x, y] = xs; return x - y; }");
+        let renamed = hashes("function f(ps) { const [p, q] = ps; return p - q; }");
+        assert_eq!(array, renamed);
+    }
+
+    #[test]
+    fn test_surrounding_declarations_ignored() {
+        let first = hashes(
+            r#"
+            const t = 1;
+            function f(x) { const t = x * 2; return t; }
+            function g(y) { const u = y * 2; return u; }
+        "#,
+        );
+        let second = hashes(
+            r#"
+            function g(y) { const u = y * 2; return u; }
+            var t = 0;
+            function f(x) { const t = x * 2; return t; }
+        "#,
+        );
+        assert_eq!(first[0], first[1]);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_bound_names_differ_from_free_ones() {
+        let bound = "function id(a) { return a; }";
+        let free = "function g(a) { return v0; }";
+        assert_ne!(hashes(bound), hashes(free));
+        // `h` + the first 16 hex digits of sha256("a")
+        assert_ne!(
+            hashes_with("function f(a) { return a; }", RenameStrategy::Hashed),
+            hashes_with("function f(a) { return hca978112ca1bbdca; }", RenameStrategy::Hashed)
+        );
+    }
+
+    #[test]
+    fn test_hashed_strategy_keeps_names_apart() {
+        let user = "function getUser(id) { return id; }";
+        let order = "function getOrder(key) { return key; }";
+        assert_eq!(hashes(user), hashes(order));
+        assert_ne!(
+            hashes_with(user, RenameStrategy::Hashed),
+            hashes_with(order, RenameStrategy::Hashed)
+        );
+
+        // Still independent of layout
+        let reformatted = "function getUser(\n    id\n) {\n    return id;\n}";
+        assert_eq!(
+            hashes_with(user, RenameStrategy::Hashed),
+            hashes_with(reformatted, RenameStrategy::Hashed)
+        );
+    }
+}
@@ -1,180 +1,527 @@
+use std::collections::HashSet;
 use swc_common::Span;
 use swc_ecma_ast::*;
 use swc_ecma_visit::{Visit, VisitWith};
 
+use crate::config::PurityConfig;
+use crate::types::type_ann_to_string;
+
 #[derive(Debug, Clone)]
 pub struct PureFunction {
     pub name: String,
     pub params: Vec<String>,
+    /// Declared type of each entry in `params`, e.g. `Some("number")`
+    pub param_types: Vec<Option<String>>,
     pub return_type: Option<String>,
     pub span: Span,
     pub is_pure: bool,
+    /// Why the function was rejected (e.g. `"dynamic-eval"`); empty when pure
+    pub impure_reasons: Vec<String>,
+    /// Enclosing binding names, outermost first
+    pub scope: Vec<String>,
+    /// Free identifiers called directly, e.g. `helper` in `helper(x)`
+    pub calls: Vec<String>,
+    /// Part of the module's public API: declared under `export`, or a
+    /// top-level binding named in `export { .. }` / `export default`
+    pub exported: bool,
+    /// Calls itself by name, directly or from a nested closure
+    pub is_recursive: bool,
+}
+
+impl PureFunction {
+    /// Scope path plus name, e.g. `handlers/onClick` or `handlers/arrow`
+    pub fn qualified_name(&self) -> String {
+        let mut parts = self.scope.clone();
+        parts.push(self.name.clone());
+        parts.join("/")
+    }
+}
+
+/// A function whose body is being visited
+struct Frame {
+    func: PureFunction,
+    external_refs: Vec<String>,
+    /// Lexical scopes inside the function, innermost last
+    bindings: Vec<HashSet<String>>,
+    /// The function's own name, e.g. `f` in `function f() {}`
+    self_name: Option<String>,
+    return_only: bool,
+    /// Slot in `functions`, so nested genes follow their parent
+    index: usize,
+}
+
+impl Frame {
+    fn binds(&self, name: &str) -> bool {
+        self.binds_local(name) || self.self_name.as_deref() == Some(name)
+    }
+
+    fn binds_local(&self, name: &str) -> bool {
+        self.bindings.iter().any(|scope| scope.contains(name))
+    }
 }
 
 pub struct PurityChecker {
-    current_function: Option<PureFunction>,
+    /// Functions being visited, innermost last
+    stack: Vec<Frame>,
     functions: Vec<PureFunction>,
-    in_function: bool,
-    has_side_effects: bool,
-    external_refs: Vec<String>,
+    class_names: Vec<String>,
+    binding_name: Option<String>,
+    /// Variable an arrow initializes, e.g. `square` in `const square = n => n * n`
+    arrow_name: Option<String>,
+    scope: Vec<String>,
+    heuristic: bool,
+    config: PurityConfig,
+    /// Inside an `export` declaration
+    exporting: bool,
+    /// Local names exported by specifier, e.g. `f` in `export { f as g }`
+    export_names: HashSet<String>,
+}
+
+impl Default for PurityChecker {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PurityChecker {
     pub fn new() -> Self {
         Self {
-            current_function: None,
+            stack: Vec::new(),
             functions: Vec::new(),
-            in_function: false,
-            has_side_effects: false,
-            external_refs: Vec::new(),
+            class_names: Vec::new(),
+            binding_name: None,
+            arrow_name: None,
+            scope: Vec::new(),
+            heuristic: false,
+            config: PurityConfig::default(),
+            exporting: false,
+            export_names: HashSet::new(),
         }
     }
     
+    /// Lenient mode: a body that is a single `return <expr>` is trusted
+    /// without external-reference analysis; impure calls still reject it
+    pub fn with_heuristic(mut self, heuristic: bool) -> Self {
+        self.heuristic = heuristic;
+        self
+    }
+    
+    /// Extra namespaces, calls and constructs to treat as pure or impure
+    pub fn with_config(mut self, config: PurityConfig) -> Self {
+        self.config = config;
+        self
+    }
+    
     pub fn extract_pure_functions(&mut self, module: &Module) -> Vec<PureFunction> {
-        module.visit_with(self);
-        
-        self.functions
-            .iter()
+        self.analyze(module)
+            .into_iter()
             .filter(|f| f.is_pure)
-            .cloned()
             .collect()
     }
     
-    fn check_purity(&self) -> bool {
-        !self.has_side_effects && self.external_refs.is_empty()
+    /// All functions found in the module, pure or not, with impurity reasons
+    pub fn analyze(&mut self, module: &Module) -> Vec<PureFunction> {
+        self.export_names = export_names(module);
+        module.visit_with(self);
+        std::mem::take(&mut self.functions)
+    }
+    
+    fn in_function(&self) -> bool {
+        !self.stack.is_empty()
+    }
+    
+    // A side effect anywhere in a body taints every enclosing function too
+    fn mark_impure(&mut self, reason: &str) {
+        for frame in &mut self.stack {
+            let reasons = &mut frame.func.impure_reasons;
+            if !reasons.iter().any(|r| r == reason) {
+                reasons.push(reason.to_string());
+            }
+        }
+    }
+    
+    fn new_function<'a>(
+        &self,
+        name: String,
+        params: impl Iterator<Item = &'a Pat>,
+        return_type: Option<&TsTypeAnn>,
+        span: Span,
+    ) -> PureFunction {
+        let (params, param_types) = params.flat_map(param_names).unzip();
+        
+        PureFunction {
+            params,
+            param_types,
+            return_type: return_type.map(type_ann_to_string),
+            span,
+            is_pure: false,
+            impure_reasons: Vec::new(),
+            scope: self.scope.clone(),
+            calls: Vec::new(),
+            exported: self.is_exported(&name),
+            is_recursive: false,
+            name,
+        }
+    }
+    
+    // Only top-level functions, keyed by their outermost binding
+    fn is_exported(&self, name: &str) -> bool {
+        if self.in_function() {
+            return false;
+        }
+        let binding = self.scope.first().or(self.class_names.first()).map_or(name, |b| b.as_str());
+        self.exporting || self.export_names.contains(binding)
+    }
+    
+    fn enter_function(
+        &mut self,
+        func: PureFunction,
+        bindings: HashSet<String>,
+        self_name: Option<String>,
+        return_only: bool,
+    ) {
+        self.stack.push(Frame {
+            func,
+            external_refs: Vec::new(),
+            bindings: vec![bindings],
+            self_name,
+            return_only,
+            index: self.functions.len(),
+        });
+    }
+    
+    fn finish_function(&mut self) {
+        if let Some(frame) = self.stack.pop() {
+            let mut func = frame.func;
+            let lenient = self.heuristic && frame.return_only;
+            if !frame.external_refs.is_empty() && !lenient {
+                func.impure_reasons.push("external-ref".to_string());
+            }
+            func.is_pure = func.impure_reasons.is_empty();
+            self.functions.insert(frame.index, func);
+        }
+    }
+
+    fn check_function(&mut self, name: String, self_name: Option<&Ident>, function: &Function) {
+        let bindings = function_bindings(
+            function.params.iter().map(|p| &p.pat),
+            function.body.as_ref(),
+        );
+        
+        let func = self.new_function(
+            name.clone(),
+            function.params.iter().map(|p| &p.pat),
+            function.return_type.as_deref(),
+            function.span,
+        );
+        // Recursion is not an external reference
+        let self_name = self_name.map(|i| i.sym.to_string());
+        self.enter_function(func, bindings, self_name, function.body.as_ref().is_some_and(is_return_only));
+        if function.is_generator && self.config.generators_impure {
+            self.mark_impure("generator");
+        }
+        
+        // Genes nested in this one are scoped under its name
+        self.scope.push(name);
+        function.visit_children_with(self);
+        self.scope.pop();
+        
+        self.finish_function();
+    }
+    
+    fn enter_class(&mut self, name: String, class: &Class) {
+        self.class_names.push(name);
+        class.visit_children_with(self);
+        self.class_names.pop();
+    }
+    
+    /// Visit with `names` bound; scopes only matter inside a function
+    fn with_scope(&mut self, names: HashSet<String>, visit: impl FnOnce(&mut Self)) {
+        if !self.in_function() {
+            visit(self);
+            return;
+        }
+        if let Some(frame) = self.stack.last_mut() {
+            frame.bindings.push(names);
+        }
+        visit(self);
+        if let Some(frame) = self.stack.last_mut() {
+            frame.bindings.pop();
+        }
+    }
+    
+    /// `ns.member` listed in `pure_members`
+    fn is_pure_member(&self, member: &MemberExpr) -> bool {
+        match (&*member.obj, &member.prop) {
+            (Expr::Ident(obj), MemberProp::Ident(prop)) => {
+                self.config.is_pure_member(&format!("{}.{}", obj.sym, prop.sym))
+            }
+            _ => false,
+        }
     }
     
-    fn reset_state(&mut self) {
-        self.has_side_effects = false;
-        self.external_refs.clear();
+    fn record_call(&mut self, name: &str) {
+        if let Some(frame) = self.stack.last_mut() {
+            if !frame.binds(name) && !frame.func.calls.iter().any(|c| c == name) {
+                frame.func.calls.push(name.to_string());
+            }
+        }
+
+        // The innermost function named `name` that no local shadows
+        for frame in self.stack.iter_mut().rev() {
+            if frame.binds_local(name) {
+                break;
+            }
+            if frame.self_name.as_deref() == Some(name) {
+                frame.func.is_recursive = true;
+                break;
+            }
+        }
     }
 }
 
 impl Visit for PurityChecker {
     fn visit_fn_decl(&mut self, node: &FnDecl) {
-        self.reset_state();
-        self.in_function = true;
-        
-        let name = node.ident.sym.to_string();
-        let params: Vec<String> = node.function.params.iter()
-            .filter_map(|p| match &p.pat {
-                Pat::Ident(ident) => Some(ident.id.sym.to_string()),
-                _ => None,
-            })
-            .collect();
+        // TypeScript overload signatures and `declare function` have no body;
+        // only the implementation is a gene
+        if node.function.body.is_none() {
+            return;
+        }
+
+        self.check_function(node.ident.sym.to_string(), Some(&node.ident), &node.function);
+    }
+    
+    fn visit_fn_expr(&mut self, node: &FnExpr) {
+        let name = node.ident
+            .as_ref()
+            .map(|i| i.sym.to_string())
+            .unwrap_or_else(|| "anonymous".to_string());
         
-        self.current_function = Some(PureFunction {
-            name: name.clone(),
-            params: params.clone(),
-            return_type: None, // TODO: extract from TypeScript types
-            span: node.function.span,
-            is_pure: false,
-        });
+        self.check_function(name, node.ident.as_ref(), &node.function);
+    }
+    
+    fn visit_arrow_expr(&mut self, node: &ArrowExpr) {
+        let return_only = match &*node.body {
+            BlockStmtOrExpr::Expr(_) => true,
+            BlockStmtOrExpr::BlockStmt(block) => is_return_only(block),
+        };
         
-        // Visit function body
-        node.function.visit_children_with(self);
+        // Named after the variable it initializes; anonymous callbacks are `arrow`
+        let binding = self.arrow_name.take();
+        let func = self.new_function(
+            binding.clone().unwrap_or_else(|| "arrow".to_string()),
+            node.params.iter(),
+            node.return_type.as_deref(),
+            node.span,
+        );
+        self.enter_function(func, arrow_bindings(node), binding.clone(), return_only);
         
-        // Check if function is pure
-        if let Some(mut func) = self.current_function.take() {
-            func.is_pure = self.check_purity();
-            self.functions.push(func);
+        let scoped = binding.is_some();
+        self.scope.extend(binding);
+        node.visit_children_with(self);
+        if scoped {
+            self.scope.pop();
         }
         
-        self.in_function = false;
+        self.finish_function();
     }
     
-    fn visit_fn_expr(&mut self, node: &FnExpr) {
-        if !self.in_function {
-            self.reset_state();
-            self.in_function = true;
-            
-            let name = node.ident
-                .as_ref()
-                .map(|i| i.sym.to_string())
-                .unwrap_or_else(|| "anonymous".to_string());
-            
-            let params: Vec<String> = node.function.params.iter()
-                .filter_map(|p| match &p.pat {
-                    Pat::Ident(ident) => Some(ident.id.sym.to_string()),
-                    _ => None,
-                })
-                .collect();
-            
-            self.current_function = Some(PureFunction {
-                name,
-                params,
-                return_type: None,
-                span: node.function.span,
-                is_pure: false,
-            });
-            
-            node.function.visit_children_with(self);
-            
-            if let Some(mut func) = self.current_function.take() {
-                func.is_pure = self.check_purity();
-                self.functions.push(func);
+    fn visit_var_declarator(&mut self, node: &VarDeclarator) {
+        // Remember the binding so `const C = class {}` and `const f = () => 1`
+        // are named C and f; other initializers see the binding as their
+        // enclosing scope
+        let mut scoped = false;
+        if let Pat::Ident(ident) = &node.name {
+            let name = ident.id.sym.to_string();
+            match node.init.as_deref() {
+                Some(Expr::Class(_)) => self.binding_name = Some(name),
+                Some(Expr::Arrow(_)) => self.arrow_name = Some(name),
+                _ => {
+                    self.scope.push(name);
+                    scoped = true;
+                }
             }
-            
-            self.in_function = false;
         }
+        node.init.visit_with(self);
+        self.binding_name = None;
+        if scoped {
+            self.scope.pop();
+        }
+        node.name.visit_with(self);
     }
     
-    fn visit_arrow_expr(&mut self, node: &ArrowExpr) {
-        if !self.in_function {
-            self.reset_state();
-            self.in_function = true;
-            
-            let params: Vec<String> = node.params.iter()
-                .filter_map(|p| match p {
-                    Pat::Ident(ident) => Some(ident.id.sym.to_string()),
-                    _ => None,
-                })
-                .collect();
-            
-            self.current_function = Some(PureFunction {
-                name: "arrow".to_string(),
-                params,
-                return_type: None,
-                span: node.span,
-                is_pure: false,
-            });
-            
+    // Nested functions and methods get their own params and `var`s; the
+    // checked function itself is scoped in `check_function`
+    fn visit_function(&mut self, node: &Function) {
+        let names = function_bindings(node.params.iter().map(|p| &p.pat), node.body.as_ref());
+        self.with_scope(names, |this| node.visit_children_with(this));
+    }
+    
+    fn visit_block_stmt(&mut self, node: &BlockStmt) {
+        let names = lexical_bindings(&node.stmts);
+        self.with_scope(names, |this| node.visit_children_with(this));
+    }
+    
+    fn visit_switch_stmt(&mut self, node: &SwitchStmt) {
+        node.discriminant.visit_with(self);
+        let names = lexical_bindings(node.cases.iter().flat_map(|c| &c.cons));
+        self.with_scope(names, |this| node.cases.visit_with(this));
+    }
+    
+    fn visit_for_stmt(&mut self, node: &ForStmt) {
+        let names = match &node.init {
+            Some(VarDeclOrExpr::VarDecl(decl)) => var_decl_bindings(decl),
+            _ => HashSet::new(),
+        };
+        self.with_scope(names, |this| node.visit_children_with(this));
+    }
+    
+    fn visit_for_in_stmt(&mut self, node: &ForInStmt) {
+        let names = for_head_bindings(&node.left);
+        self.with_scope(names, |this| node.visit_children_with(this));
+    }
+    
+    fn visit_for_of_stmt(&mut self, node: &ForOfStmt) {
+        let names = for_head_bindings(&node.left);
+        self.with_scope(names, |this| node.visit_children_with(this));
+    }
+    
+    fn visit_catch_clause(&mut self, node: &CatchClause) {
+        let mut names = HashSet::new();
+        if let Some(param) = &node.param {
+            pat_bindings(param, &mut names);
+        }
+        self.with_scope(names, |this| node.visit_children_with(this));
+    }
+    
+    // Types are erased at runtime; `T` in `identity<T>` is not a reference
+    fn visit_ts_type(&mut self, _: &TsType) {}
+    
+    fn visit_ts_type_param_decl(&mut self, _: &TsTypeParamDecl) {}
+    
+    fn visit_export_decl(&mut self, node: &ExportDecl) {
+        self.exporting = true;
+        node.visit_children_with(self);
+        self.exporting = false;
+    }
+    
+    fn visit_export_default_decl(&mut self, node: &ExportDefaultDecl) {
+        self.exporting = true;
+        node.visit_children_with(self);
+        self.exporting = false;
+    }
+    
+    fn visit_export_default_expr(&mut self, node: &ExportDefaultExpr) {
+        self.exporting = true;
+        node.visit_children_with(self);
+        self.exporting = false;
+    }
+    
+    fn visit_class_decl(&mut self, node: &ClassDecl) {
+        self.enter_class(node.ident.sym.to_string(), &node.class);
+    }
+    
+    fn visit_class_expr(&mut self, node: &ClassExpr) {
+        let name = node.ident
+            .as_ref()
+            .map(|i| i.sym.to_string())
+            .or_else(|| self.binding_name.take())
+            .unwrap_or_else(|| "anonymous".to_string());
+        
+        self.enter_class(name, &node.class);
+    }
+    
+    fn visit_class_method(&mut self, node: &ClassMethod) {
+        // Overload signatures and abstract methods, as in `visit_fn_decl`
+        if node.function.body.is_none() {
+            return;
+        }
+        if node.kind != MethodKind::Method {
             node.visit_children_with(self);
-            
-            if let Some(mut func) = self.current_function.take() {
-                func.is_pure = self.check_purity();
-                if func.is_pure {
-                    self.functions.push(func);
-                }
-            }
-            
-            self.in_function = false;
+            return;
         }
+        
+        let class = self.class_names.last().cloned().unwrap_or_else(|| "anonymous".to_string());
+        let method = match &node.key {
+            PropName::Ident(ident) => ident.sym.to_string(),
+            PropName::Str(s) => s.value.to_string(),
+            PropName::Num(n) => n.value.to_string(),
+            _ => "[computed]".to_string(),
+        };
+        
+        self.check_function(format!("{}.{}", class, method), None, &node.function);
     }
     
     // Detect side effects
     fn visit_call_expr(&mut self, node: &CallExpr) {
-        if self.in_function {
+        if self.in_function() {
             // Check for console.log, Math.random, Date.now, etc.
             if let Callee::Expr(expr) = &node.callee {
                 match &**expr {
+                    Expr::Member(member) if self.is_pure_member(member) => {}
                     Expr::Member(member) => {
+                        if let MemberProp::Ident(prop) = &member.prop {
+                            if self.config.is_mutating_method(&prop.sym) {
+                                self.mark_impure("mutation");
+                            }
+                        }
                         if let Expr::Ident(obj) = &*member.obj {
                             let obj_name = obj.sym.to_string();
-                            if matches!(obj_name.as_str(), "console" | "Math" | "Date" | "window" | "document") {
-                                self.has_side_effects = true;
+                            if matches!(obj_name.as_str(), "console" | "Date" | "window" | "document")
+                                || self.config.is_impure_namespace(&obj_name)
+                            {
+                                self.mark_impure("impure-call");
+                            }
+                            if let MemberProp::Ident(prop) = &member.prop {
+                                if self.config.is_impure_call(&format!("{}.{}", obj_name, prop.sym)) {
+                                    self.mark_impure("impure-call");
+                                }
+                            }
+                            // Math is pure apart from its RNG
+                            if obj_name == "Math" {
+                                if let MemberProp::Ident(prop) = &member.prop {
+                                    if prop.sym == *"random" {
+                                        self.mark_impure("impure-call");
+                                    }
+                                }
                             }
                         }
                     }
                     Expr::Ident(ident) => {
                         let name = ident.sym.to_string();
-                        if matches!(name.as_str(), "setTimeout" | "setInterval" | "fetch" | "require") {
-                            self.has_side_effects = true;
+                        self.record_call(&name);
+                        if matches!(name.as_str(), "setTimeout" | "setInterval" | "fetch" | "require")
+                            || self.config.is_impure_call(&name)
+                        {
+                            self.mark_impure("impure-call");
+                        }
+                        // eval() and Function() build code at runtime
+                        if matches!(name.as_str(), "eval" | "Function") {
+                            self.mark_impure("dynamic-eval");
                         }
                     }
                     _ => {}
                 }
             }
+            
+            match &node.callee {
+                // import('./x') loads a module at runtime
+                Callee::Import(_) => self.mark_impure("dynamic-import"),
+                Callee::Super(_) => self.mark_impure("impure-call"),
+                Callee::Expr(_) => {}
+            }
+        }
+        
+        node.visit_children_with(self);
+    }
+    
+    fn visit_new_expr(&mut self, node: &NewExpr) {
+        if self.in_function() {
+            if let Expr::Ident(ident) = &*node.callee {
+                if ident.sym == *"Function" {
+                    self.mark_impure("dynamic-eval");
+                }
+            }
         }
         
         node.visit_children_with(self);
@@ -182,50 +529,233 @@ impl Visit for PurityChecker {
     
     // Check for mutations
     fn visit_assign_expr(&mut self, node: &AssignExpr) {
-        if self.in_function {
-            self.has_side_effects = true;
+        if self.in_function() {
+            self.mark_impure("mutation");
         }
         node.visit_children_with(self);
     }
     
     fn visit_update_expr(&mut self, node: &UpdateExpr) {
-        if self.in_function {
-            self.has_side_effects = true;
+        if self.in_function() {
+            self.mark_impure("mutation");
+        }
+        node.visit_children_with(self);
+    }
+    
+    // `delete` mutates its operand; `typeof` and `void` only read it
+    fn visit_unary_expr(&mut self, node: &UnaryExpr) {
+        if self.in_function() && node.op == UnaryOp::Delete {
+            self.mark_impure("mutation");
         }
         node.visit_children_with(self);
     }
     
+    // Property names are not variable references; only visit computed keys
+    fn visit_member_expr(&mut self, node: &MemberExpr) {
+        // A whitelisted `Date.UTC` does not read a free `Date`
+        if !self.is_pure_member(node) {
+            node.obj.visit_with(self);
+        }
+        if let MemberProp::Computed(computed) = &node.prop {
+            computed.visit_with(self);
+        }
+    }
+    
+    fn visit_prop_name(&mut self, node: &PropName) {
+        if let PropName::Computed(computed) = node {
+            computed.visit_with(self);
+        }
+    }
+    
     // Check for external references
     fn visit_ident(&mut self, node: &Ident) {
-        if self.in_function {
-            let name = node.sym.to_string();
-            
-            // Check if it's a parameter
-            if let Some(func) = &self.current_function {
-                if !func.params.contains(&name) {
-                    // Not a parameter, might be external reference
-                    // (In real implementation, need proper scope tracking)
-                    if !is_builtin(&name) {
-                        self.external_refs.push(name);
-                    }
-                }
+        let name = node.sym.to_string();
+        if is_builtin(&name) || self.config.is_pure_namespace(&name) {
+            return;
+        }
+        
+        // Free in a function until some enclosing function binds it: an
+        // inner closure over an outer local is impure, the outer one isn't
+        for frame in self.stack.iter_mut().rev() {
+            if frame.binds(&name) {
+                break;
             }
+            frame.external_refs.push(name.clone());
         }
     }
     
+    // Methods reading or writing `this` depend on the receiver
+    fn visit_this_expr(&mut self, _: &ThisExpr) {
+        if self.in_function() {
+            self.mark_impure("this-access");
+        }
+    }
+    
+    fn visit_throw_stmt(&mut self, node: &ThrowStmt) {
+        if self.in_function() && self.config.throw_impure {
+            self.mark_impure("throw");
+        }
+        node.visit_children_with(self);
+    }
+    
     // Detect async functions
     fn visit_await_expr(&mut self, node: &AwaitExpr) {
-        if self.in_function {
-            self.has_side_effects = true;
+        if self.in_function() && self.config.async_impure {
+            self.mark_impure("async");
         }
         node.visit_children_with(self);
     }
 }
 
+/// Local names re-exported without a source: `export { a, b as c }` and
+/// `export default a`
+fn export_names(module: &Module) -> HashSet<String> {
+    let mut names = HashSet::new();
+    for item in &module.body {
+        match item {
+            ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export)) if export.src.is_none() => {
+                for specifier in &export.specifiers {
+                    if let ExportSpecifier::Named(ExportNamedSpecifier {
+                        orig: ModuleExportName::Ident(ident),
+                        ..
+                    }) = specifier
+                    {
+                        names.insert(ident.sym.to_string());
+                    }
+                }
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(export)) => {
+                if let Expr::Ident(ident) = &*export.expr {
+                    names.insert(ident.sym.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+fn is_return_only(block: &BlockStmt) -> bool {
+    matches!(block.stmts.as_slice(), [Stmt::Return(ReturnStmt { arg: Some(_), .. })])
+}
+
+/// Params plus the `var`s hoisted to the top of the body
+fn function_bindings<'a>(
+    params: impl Iterator<Item = &'a Pat>,
+    body: Option<&BlockStmt>,
+) -> HashSet<String> {
+    let mut names = HashSet::new();
+    for pat in params {
+        pat_bindings(pat, &mut names);
+    }
+    if let Some(body) = body {
+        body.visit_with(&mut VarCollector(&mut names));
+    }
+    names
+}
+
+fn arrow_bindings(node: &ArrowExpr) -> HashSet<String> {
+    let body = match &*node.body {
+        BlockStmtOrExpr::BlockStmt(block) => Some(block),
+        BlockStmtOrExpr::Expr(_) => None,
+    };
+    function_bindings(node.params.iter(), body)
+}
+
+/// `let`, `const`, function and class declarations directly in a block
+fn lexical_bindings<'a>(stmts: impl IntoIterator<Item = &'a Stmt>) -> HashSet<String> {
+    let mut names = HashSet::new();
+    for stmt in stmts {
+        match stmt {
+            Stmt::Decl(Decl::Var(decl)) if decl.kind != VarDeclKind::Var => {
+                names.extend(var_decl_bindings(decl));
+            }
+            Stmt::Decl(Decl::Fn(decl)) => {
+                names.insert(decl.ident.sym.to_string());
+            }
+            Stmt::Decl(Decl::Class(decl)) => {
+                names.insert(decl.ident.sym.to_string());
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+fn var_decl_bindings(decl: &VarDecl) -> HashSet<String> {
+    let mut names = HashSet::new();
+    for declarator in &decl.decls {
+        pat_bindings(&declarator.name, &mut names);
+    }
+    names
+}
+
+fn for_head_bindings(head: &ForHead) -> HashSet<String> {
+    match head {
+        ForHead::VarDecl(decl) => var_decl_bindings(decl),
+        _ => HashSet::new(),
+    }
+}
+
+/// Names a parameter binds, in source order, each with the type declared on
+/// the name itself; `{ a, b }: Point` gives `a` and `b` no type of their own
+fn param_names(pat: &Pat) -> Vec<(String, Option<String>)> {
+    match pat {
+        Pat::Ident(ident) => vec![(ident.id.sym.to_string(), ident.type_ann.as_deref().map(type_ann_to_string))],
+        Pat::Assign(assign) => param_names(&assign.left),
+        Pat::Rest(rest) => match &*rest.arg {
+            Pat::Ident(ident) => {
+                let ann = rest.type_ann.as_deref().or(ident.type_ann.as_deref());
+                vec![(ident.id.sym.to_string(), ann.map(type_ann_to_string))]
+            }
+            arg => param_names(arg),
+        },
+        _ => pat_names(pat).into_iter().map(|name| (name, None)).collect(),
+    }
+}
+
+/// Names bound by a pattern, e.g. `a` and `c` in `{ a, b: [c] = d }`
+fn pat_bindings(pat: &Pat, names: &mut HashSet<String>) {
+    names.extend(pat_names(pat));
+}
+
+/// `pat_bindings` in source order
+fn pat_names(pat: &Pat) -> Vec<String> {
+    let mut collector = PatCollector(Vec::new());
+    pat.visit_with(&mut collector);
+    collector.0
+}
+
+struct PatCollector(Vec<String>);
+
+impl Visit for PatCollector {
+    fn visit_binding_ident(&mut self, node: &BindingIdent) {
+        self.0.push(node.id.sym.to_string());
+    }
+    
+    // Defaults and computed keys are expressions, not bindings
+    fn visit_expr(&mut self, _: &Expr) {}
+}
+
+struct VarCollector<'a>(&'a mut HashSet<String>);
+
+impl Visit for VarCollector<'_> {
+    fn visit_var_decl(&mut self, node: &VarDecl) {
+        if node.kind == VarDeclKind::Var {
+            self.0.extend(var_decl_bindings(node));
+        }
+    }
+    
+    // Nested functions hoist their own `var`s
+    fn visit_function(&mut self, _: &Function) {}
+    
+    fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+}
+
 fn is_builtin(name: &str) -> bool {
     matches!(name, 
         "undefined" | "null" | "true" | "false" | 
-        "Array" | "Object" | "String" | "Number" | "Boolean" |
+        "Array" | "Object" | "String" | "Number" | "Boolean" | "Math" |
         "parseInt" | "parseFloat" | "isNaN" | "isFinite"
     )
 }
@@ -233,27 +763,7 @@ fn is_builtin(name: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use swc_common::sync::Lrc;
-    use swc_common::SourceMap;
-    use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax};
-    
-    fn parse_module(code: &str) -> Module {
-        let cm: Lrc<SourceMap> = Default::default();
-        let fm = cm.new_source_file(
-            swc_common::FileName::Anon,
-            code.to_string(),
-        );
-        
-        let lexer = Lexer::new(
-            Syntax::Typescript(Default::default()),
-            Default::default(),
-            StringInput::from(&*fm),
-            None,
-        );
-        
-        let mut parser = Parser::new_from(lexer);
-        parser.parse_module().unwrap()
-    }
+    use crate::test_support::parse_module;
     
     #[test]
     fn test_pure_function() {
@@ -272,6 +782,28 @@ mod tests {
         assert!(functions[0].is_pure);
     }
     
+    #[test]
+    fn test_declared_types_extracted() {
+        let code = r#"
+            function add(a: number, b: number): number {
+                return a + b;
+            }
+            
+            const names = (users: Array<{ name: string }>, sep?): string[] => users.map(u => u.name);
+        "#;
+        
+        let module = parse_module(code);
+        let functions = PurityChecker::new().analyze(&module);
+        
+        assert_eq!(functions[0].return_type, Some("number".to_string()));
+        assert_eq!(functions[0].param_types, vec![Some("number".to_string()), Some("number".to_string())]);
+        assert_eq!(functions[1].return_type, Some("string[]".to_string()));
+        assert_eq!(
+            functions[1].param_types,
+            vec![Some("Array<{ name: string }>".to_string()), None]
+        );
+    }
+    
     #[test]
     fn test_impure_console_log() {
         let code = r#"
@@ -0,0 +1,22 @@
+//! Fixtures shared by the unit tests
+
+use swc_common::sync::Lrc;
+use swc_common::SourceMap;
+use swc_ecma_ast::Module;
+use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax};
+
+/// Parse `code` as a TypeScript module; panics on a syntax error
+pub(crate) fn parse_module(code: &str) -> Module {
+    let cm: Lrc<SourceMap> = Default::default();
+    let fm = cm.new_source_file(swc_common::FileName::Anon, code.to_string());
+
+    let lexer = Lexer::new(
+        Syntax::Typescript(Default::default()),
+        Default::default(),
+        StringInput::from(&*fm),
+        None,
+    );
+
+    let mut parser = Parser::new_from(lexer);
+    parser.parse_module().unwrap()
+}
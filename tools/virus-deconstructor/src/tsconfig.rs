@@ -0,0 +1,216 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use swc_ecma_ast::EsVersion;
+use swc_ecma_parser::{Syntax, TsConfig};
+
+/// Parser-affecting subset of `tsconfig.json` compiler options
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// `experimentalDecorators`
+    pub decorators: bool,
+    /// `target`
+    pub target: EsVersion,
+}
+
+impl ParserOptions {
+    pub fn from_tsconfig(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {:?}", path))?;
+        let json: serde_json::Value = serde_json::from_str(&strip_jsonc(&raw))
+            .with_context(|| format!("Invalid tsconfig {:?}", path))?;
+
+        let options = &json["compilerOptions"];
+        let target = match options["target"].as_str() {
+            Some(t) => parse_target(t).with_context(|| format!("Unknown target {:?}", t))?,
+            None => EsVersion::default(),
+        };
+
+        Ok(Self {
+            decorators: options["experimentalDecorators"].as_bool().unwrap_or(false),
+            target,
+        })
+    }
+
+    /// JSX is on for every `.tsx` file; tsconfig's `jsx` only picks how it is emitted
+    pub fn syntax_for(&self, path: &Path) -> Syntax {
+        self.syntax(path.extension().is_some_and(|ext| ext == "tsx"))
+    }
+
+    /// TypeScript syntax with JSX on or off, other options as configured
+    pub fn syntax(&self, tsx: bool) -> Syntax {
+        Syntax::Typescript(TsConfig {
+            tsx,
+            decorators: self.decorators,
+            ..Default::default()
+        })
+    }
+}
+
+fn parse_target(target: &str) -> Option<EsVersion> {
+    Some(match target.to_ascii_lowercase().as_str() {
+        "es3" => EsVersion::Es3,
+        "es5" => EsVersion::Es5,
+        "es6" | "es2015" => EsVersion::Es2015,
+        "es2016" => EsVersion::Es2016,
+        "es2017" => EsVersion::Es2017,
+        "es2018" => EsVersion::Es2018,
+        "es2019" => EsVersion::Es2019,
+        "es2020" => EsVersion::Es2020,
+        "es2021" => EsVersion::Es2021,
+        "es2022" => EsVersion::Es2022,
+        "esnext" => EsVersion::EsNext,
+        _ => return None,
+    })
+}
+
+// tsconfig.json is JSONC: drop comments and trailing commas outside strings
+fn strip_jsonc(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut chars = src.chars().peekable();
+    let mut in_string = false;
+    // Held back until the next token shows whether it was trailing
+    let mut pending_comma = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        out.push(escaped);
+                    }
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            c if c.is_whitespace() => out.push(c),
+            '/' if chars.peek() == Some(&'/') => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+            }
+            _ => {
+                if pending_comma && c != '}' && c != ']' {
+                    out.push(',');
+                }
+                pending_comma = c == ',';
+                if !pending_comma {
+                    in_string = c == '"';
+                    out.push(c);
+                }
+            }
+        }
+    }
+
+    if pending_comma {
+        out.push(',');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swc_common::sync::Lrc;
+    use swc_common::SourceMap;
+    use swc_ecma_parser::{lexer::Lexer, Parser, StringInput};
+
+    const DECORATED: &str = r#"
+        function sealed(target) {
+            return target;
+        }
+
+        @sealed
+        class Greeter {
+            greet(name) {
+                return name;
+            }
+        }
+    "#;
+
+    fn parses(code: &str, file: &str, options: &ParserOptions) -> bool {
+        let cm: Lrc<SourceMap> = Default::default();
+        let fm = cm.new_source_file(swc_common::FileName::Anon, code.to_string());
+
+        let lexer = Lexer::new(
+            options.syntax_for(Path::new(file)),
+            options.target,
+            StringInput::from(&*fm),
+            None,
+        );
+
+        Parser::new_from(lexer).parse_module().is_ok()
+    }
+
+    fn write_tsconfig(contents: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("tsconfig.json"), contents).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_decorators_enabled_by_tsconfig() {
+        assert!(!parses(DECORATED, "a.ts", &ParserOptions::default()));
+
+        let dir = write_tsconfig(
+            r#"{
+                // JSONC comments and trailing commas are allowed
+                "compilerOptions": {
+                    "experimentalDecorators": true,
+                    "target": "ES2020",
+                },
+            }"#,
+        );
+        let options = ParserOptions::from_tsconfig(&dir.path().join("tsconfig.json")).unwrap();
+
+        assert!(options.decorators);
+        assert_eq!(options.target, EsVersion::Es2020);
+        assert!(parses(DECORATED, "a.ts", &options));
+    }
+
+    #[test]
+    fn test_trailing_comma_before_comment() {
+        let src = r#"{
+            "compilerOptions": {
+                "target": "ES2020", // note
+            },
+            "lib": ["a,b", "c" /* last */,],
+        }"#;
+        let json: serde_json::Value = serde_json::from_str(&strip_jsonc(src)).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({ "compilerOptions": { "target": "ES2020" }, "lib": ["a,b", "c"] })
+        );
+    }
+
+    #[test]
+    fn test_jsx_only_applies_to_tsx() {
+        let dir = write_tsconfig(r#"{ "compilerOptions": { "target": "ES2020" } }"#);
+        let configured = ParserOptions::from_tsconfig(&dir.path().join("tsconfig.json")).unwrap();
+        let component = "const view = (name) => <b>{name}</b>;";
+
+        // No `jsx` option needed, with or without a tsconfig
+        for options in [ParserOptions::default(), configured] {
+            assert!(parses(component, "view.tsx", &options));
+            assert!(!parses(component, "view.ts", &options));
+        }
+    }
+}
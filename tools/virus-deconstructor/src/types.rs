@@ -0,0 +1,221 @@
+use swc_ecma_ast::*;
+
+/// Canonical text of a type annotation, e.g. `number` or `Array<string>`.
+///
+/// Whitespace and comments are not preserved; parentheses are kept only
+/// where the source had them.
+pub fn type_ann_to_string(ann: &TsTypeAnn) -> String {
+    type_to_string(&ann.type_ann)
+}
+
+pub fn type_to_string(ty: &TsType) -> String {
+    match ty {
+        TsType::TsKeywordType(keyword) => keyword_name(keyword.kind).to_string(),
+        TsType::TsThisType(_) => "this".to_string(),
+        TsType::TsFnOrConstructorType(TsFnOrConstructorType::TsFnType(f)) => {
+            format!("({}) => {}", fn_params(&f.params), type_ann_to_string(&f.type_ann))
+        }
+        TsType::TsFnOrConstructorType(TsFnOrConstructorType::TsConstructorType(c)) => {
+            format!("new ({}) => {}", fn_params(&c.params), type_ann_to_string(&c.type_ann))
+        }
+        TsType::TsTypeRef(r) => format!("{}{}", entity_name(&r.type_name), type_args(r.type_params.as_deref())),
+        TsType::TsTypeQuery(q) => {
+            let name = match &q.expr_name {
+                TsTypeQueryExpr::TsEntityName(name) => entity_name(name),
+                TsTypeQueryExpr::Import(import) => import_type(import),
+            };
+            format!("typeof {}{}", name, type_args(q.type_args.as_deref()))
+        }
+        TsType::TsTypeLit(lit) => {
+            if lit.members.is_empty() {
+                return "{}".to_string();
+            }
+            let members: Vec<String> = lit.members.iter().map(type_element).collect();
+            format!("{{ {} }}", members.join("; "))
+        }
+        TsType::TsArrayType(array) => format!("{}[]", type_to_string(&array.elem_type)),
+        TsType::TsTupleType(tuple) => {
+            let elems: Vec<String> = tuple.elem_types.iter().map(|e| type_to_string(&e.ty)).collect();
+            format!("[{}]", elems.join(", "))
+        }
+        TsType::TsOptionalType(t) => format!("{}?", type_to_string(&t.type_ann)),
+        TsType::TsRestType(t) => format!("...{}", type_to_string(&t.type_ann)),
+        TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsUnionType(u)) => join(&u.types, " | "),
+        TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsIntersectionType(i)) => join(&i.types, " & "),
+        TsType::TsConditionalType(c) => format!(
+            "{} extends {} ? {} : {}",
+            type_to_string(&c.check_type),
+            type_to_string(&c.extends_type),
+            type_to_string(&c.true_type),
+            type_to_string(&c.false_type)
+        ),
+        TsType::TsInferType(infer) => format!("infer {}", type_param(&infer.type_param)),
+        TsType::TsParenthesizedType(p) => format!("({})", type_to_string(&p.type_ann)),
+        TsType::TsTypeOperator(op) => {
+            let keyword = match op.op {
+                TsTypeOperatorOp::KeyOf => "keyof",
+                TsTypeOperatorOp::Unique => "unique",
+                TsTypeOperatorOp::ReadOnly => "readonly",
+            };
+            format!("{} {}", keyword, type_to_string(&op.type_ann))
+        }
+        TsType::TsIndexedAccessType(access) => {
+            format!("{}[{}]", type_to_string(&access.obj_type), type_to_string(&access.index_type))
+        }
+        TsType::TsMappedType(mapped) => {
+            let constraint = mapped.type_param.constraint.as_deref().map(type_to_string).unwrap_or_default();
+            let value = mapped.type_ann.as_deref().map(type_to_string).unwrap_or_else(|| "any".to_string());
+            format!("{{ [{} in {}]: {} }}", mapped.type_param.name.sym, constraint, value)
+        }
+        TsType::TsLitType(lit) => match &lit.lit {
+            TsLit::Number(n) => n.value.to_string(),
+            TsLit::Str(s) => format!("\"{}\"", s.value),
+            TsLit::Bool(b) => b.value.to_string(),
+            TsLit::BigInt(b) => format!("{}n", b.value),
+            TsLit::Tpl(tpl) => {
+                let mut out = String::from("`");
+                for (i, quasi) in tpl.quasis.iter().enumerate() {
+                    out.push_str(&quasi.raw);
+                    if let Some(ty) = tpl.types.get(i) {
+                        out.push_str(&format!("${{{}}}", type_to_string(ty)));
+                    }
+                }
+                out.push('`');
+                out
+            }
+        },
+        TsType::TsTypePredicate(pred) => {
+            let param = match &pred.param_name {
+                TsThisTypeOrIdent::TsThisType(_) => "this".to_string(),
+                TsThisTypeOrIdent::Ident(ident) => ident.sym.to_string(),
+            };
+            let asserts = if pred.asserts { "asserts " } else { "" };
+            match &pred.type_ann {
+                Some(ann) => format!("{}{} is {}", asserts, param, type_ann_to_string(ann)),
+                None => format!("{}{}", asserts, param),
+            }
+        }
+        TsType::TsImportType(import) => import_type(import),
+    }
+}
+
+fn keyword_name(kind: TsKeywordTypeKind) -> &'static str {
+    match kind {
+        TsKeywordTypeKind::TsAnyKeyword => "any",
+        TsKeywordTypeKind::TsUnknownKeyword => "unknown",
+        TsKeywordTypeKind::TsNumberKeyword => "number",
+        TsKeywordTypeKind::TsObjectKeyword => "object",
+        TsKeywordTypeKind::TsBooleanKeyword => "boolean",
+        TsKeywordTypeKind::TsBigIntKeyword => "bigint",
+        TsKeywordTypeKind::TsStringKeyword => "string",
+        TsKeywordTypeKind::TsSymbolKeyword => "symbol",
+        TsKeywordTypeKind::TsVoidKeyword => "void",
+        TsKeywordTypeKind::TsUndefinedKeyword => "undefined",
+        TsKeywordTypeKind::TsNullKeyword => "null",
+        TsKeywordTypeKind::TsNeverKeyword => "never",
+        TsKeywordTypeKind::TsIntrinsicKeyword => "intrinsic",
+    }
+}
+
+fn join(types: &[Box<TsType>], sep: &str) -> String {
+    types.iter().map(|t| type_to_string(t)).collect::<Vec<_>>().join(sep)
+}
+
+fn entity_name(name: &TsEntityName) -> String {
+    match name {
+        TsEntityName::Ident(ident) => ident.sym.to_string(),
+        TsEntityName::TsQualifiedName(q) => format!("{}.{}", entity_name(&q.left), q.right.sym),
+    }
+}
+
+fn type_args(args: Option<&TsTypeParamInstantiation>) -> String {
+    match args {
+        Some(args) => format!("<{}>", join(&args.params, ", ")),
+        None => String::new(),
+    }
+}
+
+fn type_param(param: &TsTypeParam) -> String {
+    match &param.constraint {
+        Some(constraint) => format!("{} extends {}", param.name.sym, type_to_string(constraint)),
+        None => param.name.sym.to_string(),
+    }
+}
+
+fn import_type(import: &TsImportType) -> String {
+    let qualifier = import
+        .qualifier
+        .as_ref()
+        .map(|q| format!(".{}", entity_name(q)))
+        .unwrap_or_default();
+    format!("import(\"{}\"){}{}", import.arg.value, qualifier, type_args(import.type_args.as_deref()))
+}
+
+fn annotated(name: String, ann: Option<&TsTypeAnn>) -> String {
+    match ann {
+        Some(ann) => format!("{}: {}", name, type_ann_to_string(ann)),
+        None => name,
+    }
+}
+
+fn fn_params(params: &[TsFnParam]) -> String {
+    let params: Vec<String> = params
+        .iter()
+        .map(|param| match param {
+            TsFnParam::Ident(ident) => {
+                let optional = if ident.id.optional { "?" } else { "" };
+                annotated(format!("{}{}", ident.id.sym, optional), ident.type_ann.as_deref())
+            }
+            TsFnParam::Rest(rest) => {
+                let name = match &*rest.arg {
+                    Pat::Ident(ident) => ident.id.sym.to_string(),
+                    _ => "args".to_string(),
+                };
+                annotated(format!("...{}", name), rest.type_ann.as_deref())
+            }
+            TsFnParam::Array(array) => annotated("[]".to_string(), array.type_ann.as_deref()),
+            TsFnParam::Object(object) => annotated("{}".to_string(), object.type_ann.as_deref()),
+        })
+        .collect();
+    params.join(", ")
+}
+
+fn property_key(key: &Expr) -> String {
+    match key {
+        Expr::Ident(ident) => ident.sym.to_string(),
+        Expr::Lit(Lit::Str(s)) => format!("\"{}\"", s.value),
+        Expr::Lit(Lit::Num(n)) => n.value.to_string(),
+        _ => "[computed]".to_string(),
+    }
+}
+
+fn type_element(member: &TsTypeElement) -> String {
+    match member {
+        TsTypeElement::TsPropertySignature(prop) => {
+            let readonly = if prop.readonly { "readonly " } else { "" };
+            let optional = if prop.optional { "?" } else { "" };
+            let name = format!("{}{}{}", readonly, property_key(&prop.key), optional);
+            annotated(name, prop.type_ann.as_deref())
+        }
+        TsTypeElement::TsMethodSignature(method) => {
+            let optional = if method.optional { "?" } else { "" };
+            let name = format!("{}{}({})", property_key(&method.key), optional, fn_params(&method.params));
+            annotated(name, method.type_ann.as_deref())
+        }
+        TsTypeElement::TsIndexSignature(index) => {
+            annotated(format!("[{}]", fn_params(&index.params)), index.type_ann.as_deref())
+        }
+        TsTypeElement::TsCallSignatureDecl(call) => {
+            annotated(format!("({})", fn_params(&call.params)), call.type_ann.as_deref())
+        }
+        TsTypeElement::TsConstructSignatureDecl(ctor) => {
+            annotated(format!("new ({})", fn_params(&ctor.params)), ctor.type_ann.as_deref())
+        }
+        TsTypeElement::TsGetterSignature(getter) => {
+            annotated(format!("get {}()", property_key(&getter.key)), getter.type_ann.as_deref())
+        }
+        TsTypeElement::TsSetterSignature(setter) => {
+            format!("set {}({})", property_key(&setter.key), fn_params(std::slice::from_ref(&setter.param)))
+        }
+    }
+}
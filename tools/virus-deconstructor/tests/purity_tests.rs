@@ -1,4 +1,5 @@
 use virus_deconstructor::purity::{PurityChecker, PureFunction};
+use swc_ecma_ast::Module;
 
 #[test]
 fn test_pure_math_functions() {
@@ -98,12 +99,446 @@ fn test_pure_array_operations() {
     "#;
     
     let functions = extract_functions(code);
-    assert_eq!(functions.len(), 3);
+    assert_eq!(functions.len(), 4); // sum's reducer callback is a gene too
     assert!(functions.iter().all(|f| f.is_pure));
 }
 
-// Helper function
+#[test]
+fn test_overloaded_function_single_gene() {
+    let code = r#"
+        function pad(value: string): string;
+        function pad(value: number): string;
+        function pad(value) {
+            return value + " ";
+        }
+    "#;
+    
+    let functions = extract_functions(code);
+    assert_eq!(functions.len(), 1);
+    assert_eq!(functions[0].name, "pad");
+    assert_eq!(functions[0].params, vec!["value"]);
+}
+
+#[test]
+fn test_overloaded_method_single_gene() {
+    let code = r#"
+        abstract class Shape {
+            abstract area(): number;
+            scale(factor: number): number;
+            scale(factor: string): number;
+            scale(factor) {
+                return factor * 2;
+            }
+        }
+    "#;
+    
+    let functions = extract_functions(code);
+    let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["Shape.scale"]);
+    assert_eq!(functions[0].params, vec!["factor"]);
+}
+
+#[test]
+fn test_math_calls_pure_except_random() {
+    let code = r#"
+        function hypot(x, y) {
+            return Math.sqrt(x * x + y * y);
+        }
+        
+        function clamp(v, lo, hi) {
+            return Math.max(lo, Math.min(v, hi));
+        }
+        
+        function jitter(v) {
+            return v + Math.random();
+        }
+    "#;
+    
+    let functions = extract_functions(code);
+    let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["hypot", "clamp"]);
+}
+
+#[test]
+fn test_dynamic_eval_rejected() {
+    let code = r#"
+        function run(src) {
+            return eval(src);
+        }
+        
+        function compile(body) {
+            return new Function("x", body);
+        }
+    "#;
+    
+    assert_eq!(extract_functions(code).len(), 0);
+    
+    let mut checker = PurityChecker::new();
+    let all = checker.analyze(&parse(code));
+    assert_eq!(all.len(), 2);
+    for func in &all {
+        assert!(!func.is_pure);
+        assert!(func.impure_reasons.contains(&"dynamic-eval".to_string()));
+    }
+}
+
+#[test]
+fn test_dynamic_import_impure() {
+    let code = r#"
+        async function loadHelper(name) {
+            const mod = await import('./helpers');
+            return mod;
+        }
+        
+        function lazy(path) {
+            return import(path);
+        }
+    "#;
+    
+    assert_eq!(extract_functions(code).len(), 0);
+    
+    let mut checker = PurityChecker::new();
+    let all = checker.analyze(&parse(code));
+    assert!(all.iter().all(|f| f.impure_reasons.contains(&"dynamic-import".to_string())));
+}
+
+#[test]
+fn test_class_expression_methods() {
+    let code = r#"
+        const Shape = class {
+            area(w, h) {
+                return w * h;
+            }
+        };
+        
+        const Named = class Inner {
+            twice(x) {
+                return x * 2;
+            }
+        };
+    "#;
+    
+    let functions = extract_functions(code);
+    let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["Shape.area", "Inner.twice"]);
+}
+
+#[test]
+fn test_delete_impure_typeof_pure() {
+    let code = r#"
+        function drop(o) {
+            delete o.x;
+            return o;
+        }
+        
+        function kind(x) {
+            return typeof x === "number" ? void 0 : typeof x;
+        }
+    "#;
+    
+    let functions = PurityChecker::new().analyze(&parse(code));
+    assert_eq!(functions[0].impure_reasons, vec!["mutation"]);
+    assert!(functions[1].is_pure);
+}
+
+#[test]
+fn test_static_and_instance_methods() {
+    let code = r#"
+        class Vec2 {
+            constructor(x) {
+                this.x = x;
+            }
+            
+            static dot(a, b) {
+                return a * b;
+            }
+            
+            get length() {
+                return 0;
+            }
+            
+            scale(k) {
+                return this.x * k;
+            }
+            
+            setX(v) {
+                this.x = v;
+            }
+        }
+    "#;
+    
+    let functions = PurityChecker::new().analyze(&parse(code));
+    let summary: Vec<(&str, bool)> = functions.iter().map(|f| (f.name.as_str(), f.is_pure)).collect();
+    assert_eq!(
+        summary,
+        vec![("Vec2.dot", true), ("Vec2.scale", false), ("Vec2.setX", false)]
+    );
+    assert_eq!(functions[1].impure_reasons, vec!["this-access"]);
+    assert_eq!(functions[2].impure_reasons, vec!["mutation", "this-access"]);
+}
+
+#[test]
+fn test_heuristic_return_only() {
+    let code = r#"
+        function scaled(v) {
+            return v * FACTOR;
+        }
+        
+        const offset = (v) => v + BASE;
+        
+        function logged(v) {
+            console.log(v);
+            return v;
+        }
+        
+        function noisy(v) {
+            return console.log(v);
+        }
+    "#;
+    
+    assert_eq!(extract_functions(code).len(), 0);
+    
+    let mut checker = PurityChecker::new().with_heuristic(true);
+    let functions = checker.extract_pure_functions(&parse(code));
+    let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["scaled", "offset"]);
+}
+
+#[test]
+fn test_local_bindings_are_not_external() {
+    let code = r#"
+        function sum(a, b) {
+            const tmp = a + b;
+            return tmp;
+        }
+        
+        function total(items) {
+            var acc = 0;
+            for (const { price } of items) {
+                acc = acc + price;
+            }
+            return acc;
+        }
+        
+        function identity<T>(value: T): T {
+            return value;
+        }
+    "#;
+    
+    let functions = extract_functions(code);
+    let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, vec!["sum", "identity"]);
+    
+    // `total` is only rejected for the reassignment, not for its locals
+    let total = PurityChecker::new()
+        .analyze(&parse(code))
+        .into_iter()
+        .find(|f| f.name == "total")
+        .unwrap();
+    assert_eq!(total.impure_reasons, vec!["mutation"]);
+}
+
+#[test]
+fn test_module_level_read_stays_impure() {
+    let code = r#"
+        const rate = 0.2;
+        
+        function tax(amount) {
+            return amount * rate;
+        }
+        
+        function leaky(a) {
+            {
+                const inner = a;
+            }
+            return inner;
+        }
+    "#;
+    
+    assert_eq!(extract_functions(code).len(), 0);
+}
+
+#[test]
+fn test_nested_functions_extracted() {
+    let code = r#"
+        function outer() {
+            const inner = x => x * 2;
+            return inner;
+        }
+        
+        function adder(n) {
+            return function add(x) {
+                return x + n;
+            };
+        }
+        
+        function logsLater() {
+            const cb = () => console.log(1);
+            return cb;
+        }
+    "#;
+    
+    let functions = PurityChecker::new().analyze(&parse(code));
+    let summary: Vec<(String, bool)> = functions
+        .iter()
+        .map(|f| (f.qualified_name(), f.is_pure))
+        .collect();
+    
+    assert_eq!(
+        summary,
+        vec![
+            ("outer".to_string(), true),
+            ("outer/inner".to_string(), true),
+            // Closing over `n` is external to `add`, local to `adder`
+            ("adder".to_string(), true),
+            ("adder/add".to_string(), false),
+            // The callback's side effect taints its parent
+            ("logsLater".to_string(), false),
+            ("logsLater/cb".to_string(), false),
+        ]
+    );
+}
+
+#[test]
+fn test_exported_functions_flagged() {
+    let code = r#"
+        export function a(x) { return x; }
+        export const b = (x) => x;
+        export default function (x) { return x; }
+        function c(x) { return x; }
+        function d(x) { return x; }
+        const e = (x) => x;
+        export { c, e as renamed };
+    "#;
+    
+    let exported: Vec<String> = PurityChecker::new()
+        .analyze(&parse(code))
+        .iter()
+        .filter(|f| f.exported)
+        .map(|f| f.qualified_name())
+        .collect();
+    assert_eq!(exported, vec!["a", "b", "anonymous", "c", "e"]);
+}
+
+#[test]
+fn test_recursion_flagged() {
+    let code = r#"
+        function factorial(n) {
+            if (n <= 1) return 1;
+            return n * factorial(n - 1);
+        }
+        
+        function sumTree(node) {
+            return node.children.reduce((acc, child) => acc + sumTree(child), node.value);
+        }
+        
+        function twice(x) {
+            return double(double(x));
+        }
+        
+        function shadowed(f) {
+            const shadowed = f;
+            return shadowed(1);
+        }
+    "#;
+    
+    let functions = PurityChecker::new().analyze(&parse(code));
+    let recursive: Vec<(String, bool)> = functions
+        .iter()
+        .map(|f| (f.qualified_name(), f.is_recursive))
+        .collect();
+    assert_eq!(recursive, vec![
+        ("factorial".to_string(), true),
+        ("sumTree".to_string(), true),
+        ("sumTree/arrow".to_string(), false),
+        ("twice".to_string(), false),
+        ("shadowed".to_string(), false),
+    ]);
+    
+    // A self-call is not an external reference
+    assert!(functions[0].is_pure);
+    assert!(functions[0].calls.is_empty());
+    assert!(functions[1].is_pure);
+    assert!(!functions[3].is_pure);
+}
+
+#[test]
+fn test_arrows_named_after_binding() {
+    let code = r#"
+        const square = n => n * n;
+        
+        const inc = (n) => {
+            const step = 1;
+            return n + step;
+        };
+        
+        const shout = s => console.log(s);
+        
+        const warn = (s) => {
+            console.log(s);
+            return s;
+        };
+        
+        const handlers = {
+            onClick: (e) => e.target,
+        };
+        
+        const fact = n => n <= 1 ? 1 : n * fact(n - 1);
+    "#;
+    
+    let functions = PurityChecker::new().analyze(&parse(code));
+    let summary: Vec<(String, bool)> = functions
+        .iter()
+        .map(|f| (f.qualified_name(), f.is_pure))
+        .collect();
+    assert_eq!(summary, vec![
+        ("square".to_string(), true),
+        ("inc".to_string(), true),
+        ("shout".to_string(), false),
+        ("warn".to_string(), false),
+        ("handlers/arrow".to_string(), true),
+        ("fact".to_string(), true),
+    ]);
+    
+    // Expression and block bodies are checked by the same rules
+    assert_eq!(functions[2].impure_reasons, functions[3].impure_reasons);
+    assert!(functions[5].is_recursive);
+}
+
+#[test]
+fn test_destructured_params() {
+    let code = r#"
+        function area({ width, height: h }: Size) {
+            return width * h;
+        }
+        
+        function swap([x, , y = 0], ...rest: number[]) {
+            return [y, x, rest];
+        }
+        
+        function scaled({ factor = 2, origin: { x } }, offset: number = 1) {
+            return factor * x + offset;
+        }
+    "#;
+    
+    let functions = PurityChecker::new().analyze(&parse(code));
+    assert!(functions.iter().all(|f| f.is_pure), "{:?}", functions);
+    
+    assert_eq!(functions[0].params, vec!["width", "h"]);
+    assert_eq!(functions[0].param_types, vec![None, None]);
+    assert_eq!(functions[1].params, vec!["x", "y", "rest"]);
+    assert_eq!(functions[1].param_types, vec![None, None, Some("number[]".to_string())]);
+    assert_eq!(functions[2].params, vec!["factor", "x", "offset"]);
+    assert_eq!(functions[2].param_types[2], Some("number".to_string()));
+}
+
+// Helper functions
 fn extract_functions(code: &str) -> Vec<PureFunction> {
+    let mut checker = PurityChecker::new();
+    checker.extract_pure_functions(&parse(code))
+}
+
+fn parse(code: &str) -> Module {
     use swc_common::sync::Lrc;
     use swc_common::SourceMap;
     use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax};
@@ -122,8 +557,5 @@ fn extract_functions(code: &str) -> Vec<PureFunction> {
     );
     
     let mut parser = Parser::new_from(lexer);
-    let module = parser.parse_module().unwrap();
-    
-    let mut checker = PurityChecker::new();
-    checker.extract_pure_functions(&module)
+    parser.parse_module().unwrap()
 }
\ No newline at end of file
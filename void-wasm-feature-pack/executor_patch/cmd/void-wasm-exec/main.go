@@ -16,6 +16,8 @@ import (
 	"net/url"
 	"os"
 	"path/filepath"
+	"sort"
+	"strconv"
 	"strings"
 	"sync"
 	"time"
@@ -306,42 +308,117 @@ func runWasm(ctx context.Context, cfg Config, path string, env *Envelope) error
 	if err := os.MkdirAll(tmpDir, 0o755); err != nil { return err }
 	defer os.RemoveAll(tmpDir)
 
-	// Inputs on stdin
+	// Inputs on stdin, then a reply per syscall
 	inputs := env.Inputs; if inputs == nil { inputs = map[string]any{} }
 	inBytes, _ := json.Marshal(inputs)
-	stdin := bytes.NewReader(inBytes)
+	session := newSysSession(cfg, inBytes)
 
-	var stdoutBuf bytes.Buffer
 	var stderrBuf bytes.Buffer
 
 	cfgMod := wazero.NewModuleConfig().
-		WithStdout(&stdoutBuf).
+		WithStdout(session).
 		WithStderr(&stderrBuf).
-		WithStdin(stdin).
+		WithStdin(session).
 		WithFSConfig(wazero.NewFSConfig().WithDir("/tmp", tmpDir))
 
 	compiled, err := r.CompileModule(ctx, mustRead(path))
 	if err != nil { return err }
 	_, err = r.InstantiateModule(ctx, compiled, cfgMod)
 	if err != nil { return err }
+	return session.finish()
+}
 
-	// Process stdout lines
-	sc := bufio.NewScanner(&stdoutBuf)
-	for sc.Scan() {
-		line := strings.TrimSpace(sc.Text())
-		if line == "" { continue }
-		var ev map[string]any
-		if err := json.Unmarshal([]byte(line), &ev); err != nil {
-			continue
-		}
-		stdoutEvents.Inc()
-		if t, _ := ev["type"].(string); strings.HasPrefix(t, "syscall.") {
-			handleSyscall(cfg, t, ev)
-		} else {
-			postEvent(cfg, ev)
-		}
+// --- Syscall stdio session ---
+// Stdout is served as the module writes it: each syscall's reply is queued
+// on stdin before the module's next read. A "syscall.abi" handshake can
+// switch both directions from lines to length-prefixed frames
+// ("<bytes>\n<json>").
+type sysSession struct {
+	cfg     Config
+	framing string
+	pending []byte
+	stdin   bytes.Buffer
+	err     error
+}
+
+func newSysSession(cfg Config, inputs []byte) *sysSession {
+	s := &sysSession{cfg: cfg, framing: "lines"}
+	s.stdin.Write(inputs)
+	return s
+}
+
+// Read is the module's stdin; io.EOF while nothing is queued
+func (s *sysSession) Read(p []byte) (int, error) { return s.stdin.Read(p) }
+
+// Write is the module's stdout
+func (s *sysSession) Write(p []byte) (int, error) {
+	s.pending = append(s.pending, p...)
+	for {
+		msg, ok, err := s.next()
+		if err != nil { s.err = err; return 0, err }
+		if !ok { return len(p), nil }
+		s.serve(msg)
+	}
+}
+
+// next cuts the first complete message off pending
+func (s *sysSession) next() ([]byte, bool, error) {
+	nl := bytes.IndexByte(s.pending, '\n')
+	if nl < 0 { return nil, false, nil }
+	if s.framing == "length" {
+		n, err := strconv.Atoi(strings.TrimSpace(string(s.pending[:nl])))
+		if err != nil || n < 0 { return nil, false, fmt.Errorf("bad frame header %q", s.pending[:nl]) }
+		if len(s.pending) < nl+1+n { return nil, false, nil }
+		msg := append([]byte(nil), s.pending[nl+1:nl+1+n]...)
+		s.pending = s.pending[nl+1+n:]
+		return msg, true, nil
 	}
-	return sc.Err()
+	msg := append([]byte(nil), s.pending[:nl]...)
+	s.pending = s.pending[nl+1:]
+	return msg, true, nil
+}
+
+// finish serves an unterminated last line once the module has exited
+func (s *sysSession) finish() error {
+	if s.err != nil { return s.err }
+	if len(s.pending) > 0 && s.framing == "length" { return errors.New("stdout ended inside a frame") }
+	if len(s.pending) > 0 { s.serve(s.pending) }
+	s.pending = nil
+	return nil
+}
+
+func (s *sysSession) serve(msg []byte) {
+	line := bytes.TrimSpace(msg)
+	if len(line) == 0 { return }
+	var ev map[string]any
+	if err := json.Unmarshal(line, &ev); err != nil { return }
+	stdoutEvents.Inc()
+	t, _ := ev["type"].(string)
+	if !strings.HasPrefix(t, "syscall.") { postEvent(s.cfg, ev); return }
+	if t == "syscall.abi" {
+		// the reply still goes out in the old framing
+		framing, _ := ev["framing"].(string)
+		ok := framing == "lines" || framing == "length"
+		if !ok { framing = s.framing }
+		s.reply(map[string]any{"type":"sysret.abi","ok":ok,"framing":framing})
+		s.framing = framing
+		return
+	}
+	if reply := handleSyscall(s.cfg, t, ev); reply != nil {
+		postEvent(s.cfg, reply)
+		s.reply(reply)
+	}
+}
+
+func (s *sysSession) reply(ev map[string]any) {
+	b, _ := json.Marshal(ev)
+	if s.framing == "length" {
+		fmt.Fprintf(&s.stdin, "%d\n", len(b))
+		s.stdin.Write(b)
+		return
+	}
+	s.stdin.Write(b)
+	s.stdin.WriteByte('\n')
 }
 
 var httpClient = &http.Client{ Timeout: 2 * time.Second, Transport: &http.Transport{
@@ -349,7 +426,8 @@ var httpClient = &http.Client{ Timeout: 2 * time.Second, Transport: &http.Transp
 	DisableKeepAlives: true,
 }}
 
-func handleSyscall(cfg Config, kind string, payload map[string]any) {
+// handleSyscall performs one syscall; reply is its sysret, nil if there is none
+func handleSyscall(cfg Config, kind string, payload map[string]any) (reply map[string]any) {
 	t0 := time.Now()
 	result := "ok"
 	defer func(){ sysReqTotal.WithLabelValues(kind, result).Inc(); sysDur.WithLabelValues(kind).Observe(float64(time.Since(t0).Milliseconds())) }()
@@ -369,13 +447,32 @@ func handleSyscall(cfg Config, kind string, payload map[string]any) {
 		if key == "" { result = "bad_key"; return }
 		m[key] = val
 		if err := kvSave(m); err != nil { result = "io_err"; return }
-		postEvent(cfg, map[string]any{"type":"sysret.kv.set","ok":true,"key":key})
+		reply = map[string]any{"type":"sysret.kv.set","ok":true,"key":key}
 	case "syscall.kv.get":
 		if !allowed("kv", cfg.AllowCaps) { result = "denied"; return }
 		m := kvLoad()
 		key, _ := payload["key"].(string)
 		val := m[key]
-		postEvent(cfg, map[string]any{"type":"sysret.kv.get","ok": val != nil, "key": key, "value": val})
+		reply = map[string]any{"type":"sysret.kv.get","ok": val != nil, "key": key, "value": val}
+	case "syscall.kv.delete":
+		if !allowed("kv", cfg.AllowCaps) { result = "denied"; return }
+		m := kvLoad()
+		key, _ := payload["key"].(string)
+		if key == "" { result = "bad_key"; return }
+		_, existed := m[key]
+		delete(m, key)
+		if err := kvSave(m); err != nil { result = "io_err"; return }
+		reply = map[string]any{"type":"sysret.kv.delete","ok":existed,"key":key}
+	case "syscall.kv.list":
+		if !allowed("kv", cfg.AllowCaps) { result = "denied"; return }
+		m := kvLoad()
+		prefix, _ := payload["prefix"].(string)
+		keys := []string{}
+		for k := range m {
+			if strings.HasPrefix(k, prefix) { keys = append(keys, k) }
+		}
+		sort.Strings(keys)
+		reply = map[string]any{"type":"sysret.kv.list","ok":true,"prefix":prefix,"keys":keys}
 	case "syscall.http.fetch":
 		if !allowed("http", cfg.AllowCaps) { result = "denied"; return }
 		reqMap, _ := payload["req"].(map[string]any)
@@ -404,13 +501,14 @@ func handleSyscall(cfg Config, kind string, payload map[string]any) {
 		}
 		limited := io.LimitedReader{ R: resp.Body, N: int64(limKB)*1024 }
 		n, _ := io.Copy(io.Discard, &limited)
-		postEvent(cfg, map[string]any{
+		reply = map[string]any{
 			"type":"sysret.http","id":id,"status":resp.StatusCode,
 			"kb": n/1024, "headers": map[string]any{"content-type": resp.Header.Get("content-type")},
-		})
+		}
 	default:
 		result = "unknown"
 	}
+	return
 }
 
 func mustRead(path string) []byte { b, err := os.ReadFile(path); if err != nil { panic(err) }; return b }
@@ -0,0 +1,94 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// How messages are delimited on a module's stdin and stdout, agreed with
+/// a `syscall.abi` handshake (docs/SYSCALLS.md)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Framing {
+    /// One JSON object per line
+    #[default]
+    Lines,
+    /// A decimal byte count and `\n`, then that many bytes of JSON; the
+    /// payload may span lines or be arbitrarily large
+    Length,
+}
+
+impl Framing {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "lines" => Some(Self::Lines),
+            "length" => Some(Self::Length),
+            _ => None,
+        }
+    }
+
+    pub fn encode(self, payload: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Lines => {
+                let mut out = payload.to_vec();
+                out.push(b'\n');
+                out
+            }
+            Self::Length => {
+                let mut out = format!("{}\n", payload.len()).into_bytes();
+                out.extend_from_slice(payload);
+                out
+            }
+        }
+    }
+
+    /// The first complete message in `buf` and the bytes it spans, or
+    /// `None` while it is still arriving
+    pub fn decode(self, buf: &[u8]) -> Result<Option<(&[u8], usize)>> {
+        let Some(newline) = buf.iter().position(|&b| b == b'\n') else {
+            return Ok(None);
+        };
+        match self {
+            Self::Lines => Ok(Some((&buf[..newline], newline + 1))),
+            Self::Length => {
+                let header = &buf[..newline];
+                let len: usize = std::str::from_utf8(header)
+                    .ok()
+                    .and_then(|h| h.trim().parse().ok())
+                    .with_context(|| format!("Bad frame header {:?}", String::from_utf8_lossy(header)))?;
+                let end = newline + 1 + len;
+                if buf.len() < end {
+                    return Ok(None);
+                }
+                Ok(Some((&buf[newline + 1..end], end)))
+            }
+        }
+    }
+
+    /// What is left in the buffer when the stream ends: an unterminated
+    /// line still counts, a cut-off frame is an error
+    pub fn decode_last(self, buf: &[u8]) -> Result<Option<&[u8]>> {
+        if buf.is_empty() {
+            return Ok(None);
+        }
+        match self {
+            Self::Lines => Ok(Some(buf)),
+            Self::Length => bail!("Stream ended inside a frame"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_waits_for_whole_frame() {
+        let framed = Framing::Length.encode(b"{\n}");
+        assert_eq!(framed, b"3\n{\n}");
+
+        assert_eq!(Framing::Length.decode(&framed[..4]).unwrap(), None);
+        assert_eq!(Framing::Length.decode(&framed).unwrap(), Some((&b"{\n}"[..], 5)));
+        assert_eq!(Framing::Lines.decode(&framed).unwrap(), Some((&b"3"[..], 2)));
+
+        assert!(Framing::Length.decode(b"{}\n").is_err());
+        assert!(Framing::Length.decode_last(&framed[..4]).is_err());
+        assert_eq!(Framing::Lines.decode_last(b"{}").unwrap(), Some(&b"{}"[..]));
+    }
+}
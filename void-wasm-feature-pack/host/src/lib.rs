@@ -0,0 +1,349 @@
+//! Rust host for void wasm modules: runs a WASI command module, serves the
+//! NDJSON syscalls it prints from an in-memory KV store and writes each reply
+//! back to the module's stdin.
+
+pub mod framing;
+#[cfg(feature = "wasmtime")]
+mod runtime;
+pub mod syscall;
+
+pub use framing::Framing;
+#[cfg(feature = "wasmtime")]
+pub use runtime::{run_module, run_module_with};
+pub use syscall::{Syscall, SyscallResult};
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+
+/// In-memory stand-in for the executor's `/tmp/void/kv.json`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct KvStore {
+    entries: HashMap<String, Value>,
+}
+
+impl KvStore {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries.get(key)
+    }
+
+    pub fn dispatch(&mut self, call: &Syscall) -> SyscallResult {
+        match call {
+            Syscall::KvSet { key, value } => {
+                self.entries.insert(key.clone(), value.clone());
+                SyscallResult::KvSet {
+                    ok: true,
+                    key: key.clone(),
+                }
+            }
+            Syscall::KvGet { key } => {
+                let value = self.entries.get(key).cloned();
+                SyscallResult::KvGet {
+                    ok: value.is_some(),
+                    key: key.clone(),
+                    value: value.unwrap_or(Value::Null),
+                }
+            }
+            Syscall::KvDelete { key } => SyscallResult::KvDelete {
+                ok: self.entries.remove(key).is_some(),
+                key: key.clone(),
+            },
+            Syscall::KvList { prefix } => {
+                let mut keys: Vec<String> = self
+                    .entries
+                    .keys()
+                    .filter(|key| key.starts_with(prefix.as_str()))
+                    .cloned()
+                    .collect();
+                keys.sort();
+                SyscallResult::KvList {
+                    ok: true,
+                    prefix: prefix.clone(),
+                    keys,
+                }
+            }
+            // Framing belongs to the Session, not the store
+            Syscall::Abi { .. } => SyscallResult::Unsupported {
+                syscall: "syscall.abi".to_string(),
+            },
+            Syscall::Unsupported(kind) => SyscallResult::Unsupported {
+                syscall: kind.clone(),
+            },
+        }
+    }
+}
+
+/// One module run as seen from the host: stdout goes in as the module
+/// writes it, and each syscall is served as soon as its message is
+/// complete, so the reply is on stdin before the module's next read.
+#[derive(Debug, Default)]
+pub struct Session {
+    kv: KvStore,
+    framing: Framing,
+    /// Stdout not yet making up a whole message
+    partial: Vec<u8>,
+    messages: usize,
+    /// The run's input, then one reply per syscall
+    stdin: VecDeque<u8>,
+    results: Vec<SyscallResult>,
+}
+
+impl Session {
+    pub fn new(kv: KvStore, stdin: &[u8]) -> Self {
+        Self {
+            kv,
+            stdin: stdin.iter().copied().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Take bytes the module wrote to stdout
+    pub fn write_stdout(&mut self, bytes: &[u8]) -> Result<()> {
+        self.partial.extend_from_slice(bytes);
+        // The framing can change after any message, so decode one at a time
+        while let Some((message, len)) = self.framing.decode(&self.partial)? {
+            let message = message.to_vec();
+            self.partial.drain(..len);
+            self.serve(&message)?;
+        }
+        Ok(())
+    }
+
+    /// Fill `buf` from stdin; 0 means nothing is queued yet, which the
+    /// module sees as end of file
+    pub fn read_stdin(&mut self, buf: &mut [u8]) -> usize {
+        let n = buf.len().min(self.stdin.len());
+        for (slot, byte) in buf.iter_mut().zip(self.stdin.drain(..n)) {
+            *slot = byte;
+        }
+        n
+    }
+
+    /// End of the run: an unterminated last line is still served
+    pub fn finish(&mut self) -> Result<()> {
+        let partial = std::mem::take(&mut self.partial);
+        match self.framing.decode_last(&partial)? {
+            Some(message) => self.serve(message),
+            None => Ok(()),
+        }
+    }
+
+    /// The store with every syscall served so far applied, and their results
+    pub fn into_parts(self) -> (KvStore, Vec<SyscallResult>) {
+        (self.kv, self.results)
+    }
+
+    fn serve(&mut self, message: &[u8]) -> Result<()> {
+        self.messages += 1;
+        let message = String::from_utf8_lossy(message);
+        let call = Syscall::parse(&message).with_context(|| format!("stdout message {}", self.messages))?;
+        let Some(call) = call else {
+            return Ok(());
+        };
+
+        let result = match &call {
+            Syscall::Abi { framing } => SyscallResult::Abi {
+                ok: Framing::parse(framing).is_some(),
+                framing: Framing::parse(framing).unwrap_or(self.framing),
+            },
+            _ => self.kv.dispatch(&call),
+        };
+        // The handshake reply still goes out in the old framing
+        self.stdin.extend(self.framing.encode(&serde_json::to_vec(&result)?));
+        if let SyscallResult::Abi { framing, .. } = result {
+            self.framing = framing;
+        }
+        self.results.push(result);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn serve(stdout: &[&str]) -> Vec<SyscallResult> {
+        let mut session = Session::default();
+        session.write_stdout(stdout.join("\n").as_bytes()).unwrap();
+        session.finish().unwrap();
+        session.into_parts().1
+    }
+
+    #[test]
+    fn test_get_sees_earlier_set() {
+        let mut session = Session::default();
+        session
+            .write_stdout(
+                [
+                    r#"{"type":"syscall.kv.get","key":"note/last"}"#,
+                    "plain log line",
+                    r#"{"type":"syscall.kv.set","key":"note/last","value":{"msg":"hi"}}"#,
+                    r#"{"type":"syscall.kv.get","key":"note/last"}"#,
+                ]
+                .join("\n")
+                .as_bytes(),
+            )
+            .unwrap();
+        session.finish().unwrap();
+        let (kv, results) = session.into_parts();
+
+        assert_eq!(
+            results,
+            vec![
+                SyscallResult::KvGet {
+                    ok: false,
+                    key: "note/last".to_string(),
+                    value: Value::Null,
+                },
+                SyscallResult::KvSet {
+                    ok: true,
+                    key: "note/last".to_string(),
+                },
+                SyscallResult::KvGet {
+                    ok: true,
+                    key: "note/last".to_string(),
+                    value: json!({ "msg": "hi" }),
+                },
+            ]
+        );
+        assert_eq!(kv.get("note/last"), Some(&json!({ "msg": "hi" })));
+    }
+
+    #[test]
+    fn test_reply_queued_before_next_read() {
+        let mut session = Session::new(KvStore::default(), b"{}");
+        let mut buf = [0u8; 256];
+
+        // Input first, then nothing until the module asks for something
+        assert_eq!(session.read_stdin(&mut buf), 2);
+        assert_eq!(session.read_stdin(&mut buf), 0);
+
+        // Half a line is not served yet
+        session.write_stdout(br#"{"type":"syscall.kv.get","#).unwrap();
+        assert_eq!(session.read_stdin(&mut buf), 0);
+
+        session.write_stdout(b"\"key\":\"k\"}\n").unwrap();
+        let n = session.read_stdin(&mut buf);
+        assert_eq!(
+            std::str::from_utf8(&buf[..n]).unwrap(),
+            "{\"type\":\"sysret.kv.get\",\"ok\":false,\"key\":\"k\",\"value\":null}\n"
+        );
+    }
+
+    #[test]
+    fn test_length_prefixed_round_trip() {
+        let mut session = Session::default();
+        session
+            .write_stdout(b"{\"type\":\"syscall.abi\",\"framing\":\"length\"}\n")
+            .unwrap();
+
+        // Pretty-printed, so the payload itself spans lines; several MiB
+        let value = json!({ "blob": "0123456789abcdef".repeat(1 << 18), "lines": ["a", "b"] });
+        let set = json!({ "type": "syscall.kv.set", "key": "big", "value": value });
+        let mut stdout = Framing::Length.encode(&serde_json::to_vec_pretty(&set).unwrap());
+        stdout.extend(Framing::Length.encode(br#"{"type":"syscall.kv.get","key":"big"}"#));
+        for chunk in stdout.chunks(4096) {
+            session.write_stdout(chunk).unwrap();
+        }
+
+        let mut stdin = vec![0u8; 8 << 20];
+        let n = session.read_stdin(&mut stdin);
+        let mut stdin = &stdin[..n];
+        let mut replies = Vec::new();
+        for framing in [Framing::Lines, Framing::Length, Framing::Length] {
+            let (reply, len) = framing.decode(stdin).unwrap().unwrap();
+            replies.push(serde_json::from_slice::<SyscallResult>(reply).unwrap());
+            stdin = &stdin[len..];
+        }
+        assert!(stdin.is_empty());
+
+        assert_eq!(
+            replies,
+            vec![
+                SyscallResult::Abi {
+                    ok: true,
+                    framing: Framing::Length,
+                },
+                SyscallResult::KvSet {
+                    ok: true,
+                    key: "big".to_string(),
+                },
+                SyscallResult::KvGet {
+                    ok: true,
+                    key: "big".to_string(),
+                    value,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_framing_declined() {
+        let results = serve(&[
+            r#"{"type":"syscall.abi","framing":"cbor"}"#,
+            r#"{"type":"syscall.kv.get","key":"k"}"#,
+        ]);
+        assert_eq!(
+            results[0],
+            SyscallResult::Abi {
+                ok: false,
+                framing: Framing::Lines,
+            }
+        );
+        // Still line framed
+        assert!(matches!(results[1], SyscallResult::KvGet { ok: false, .. }));
+    }
+
+    #[test]
+    fn test_malformed_syscall_names_line() {
+        let mut session = Session::default();
+        let err = session
+            .write_stdout(b"log\n{\"type\":\"syscall.kv.get\"}\n")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "stdout message 2");
+    }
+
+    #[test]
+    fn test_delete_and_list() {
+        let results = serve(&[
+            r#"{"type":"syscall.kv.set","key":"note/b","value":2}"#,
+            r#"{"type":"syscall.kv.set","key":"todo/x","value":0}"#,
+            r#"{"type":"syscall.kv.set","key":"note/a","value":1}"#,
+            r#"{"type":"syscall.kv.list","prefix":"note/"}"#,
+            r#"{"type":"syscall.kv.delete","key":"note/a"}"#,
+            r#"{"type":"syscall.kv.delete","key":"note/a"}"#,
+            r#"{"type":"syscall.kv.get","key":"note/a"}"#,
+            r#"{"type":"syscall.kv.list"}"#,
+        ]);
+
+        assert_eq!(
+            results[3..],
+            [
+                SyscallResult::KvList {
+                    ok: true,
+                    prefix: "note/".to_string(),
+                    keys: vec!["note/a".to_string(), "note/b".to_string()],
+                },
+                SyscallResult::KvDelete {
+                    ok: true,
+                    key: "note/a".to_string(),
+                },
+                SyscallResult::KvDelete {
+                    ok: false,
+                    key: "note/a".to_string(),
+                },
+                SyscallResult::KvGet {
+                    ok: false,
+                    key: "note/a".to_string(),
+                    value: Value::Null,
+                },
+                SyscallResult::KvList {
+                    ok: true,
+                    prefix: String::new(),
+                    keys: vec!["note/b".to_string(), "todo/x".to_string()],
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{KvStore, Session, SyscallResult};
+use anyhow::{Context, Result};
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+use wasi_common::pipe::{ReadPipe, WritePipe};
+use wasmtime::{Engine, Linker, Module, Store};
+use wasmtime_wasi::sync::WasiCtxBuilder;
+use wasmtime_wasi::WasiCtx;
+
+/// Run a module against a fresh, empty store
+pub fn run_module(wasm_bytes: &[u8], stdin: &[u8]) -> Result<Vec<SyscallResult>> {
+    run_module_with(&mut KvStore::default(), wasm_bytes, stdin)
+}
+
+/// Run a module against `kv`, which keeps its entries for later runs
+pub fn run_module_with(kv: &mut KvStore, wasm_bytes: &[u8], stdin: &[u8]) -> Result<Vec<SyscallResult>> {
+    let shared = Shared::new(Session::new(std::mem::take(kv), stdin));
+    let run = execute(wasm_bytes, &shared);
+
+    let SharedState { mut session, error } = Arc::try_unwrap(shared.0)
+        .map_err(|_| anyhow::anyhow!("stdio pipes still shared"))?
+        .into_inner()
+        .unwrap();
+    let finished = session.finish();
+    let (store, results) = session.into_parts();
+    *kv = store;
+
+    // A malformed syscall fails the module's write; report it, not the trap that follows
+    if let Some(error) = error {
+        return Err(error);
+    }
+    run?;
+    finished?;
+    Ok(results)
+}
+
+/// Instantiate and run the module's `_start` with its stdio wired to `shared`
+fn execute(wasm_bytes: &[u8], shared: &Shared) -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, wasm_bytes).context("Invalid wasm module")?;
+
+    let mut linker: Linker<WasiCtx> = Linker::new(&engine);
+    wasmtime_wasi::sync::add_to_linker(&mut linker, |cx| cx)?;
+
+    let wasi = WasiCtxBuilder::new()
+        .stdin(Box::new(ReadPipe::new(Stdin(shared.clone()))))
+        .stdout(Box::new(WritePipe::new(Stdout(shared.clone()))))
+        .inherit_stderr()
+        .build();
+    let mut store = Store::new(&engine, wasi);
+
+    linker.module(&mut store, "", &module)?;
+    let start = linker.get_default(&mut store, "")?.typed::<(), ()>(&store)?;
+    if let Err(trap) = start.call(&mut store, ()) {
+        // `proc_exit(0)` unwinds as an error too
+        match trap.downcast_ref::<wasi_common::I32Exit>() {
+            Some(exit) if exit.0 == 0 => {}
+            _ => return Err(trap.context("Module trapped")),
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+struct Shared(Arc<Mutex<SharedState>>);
+
+struct SharedState {
+    session: Session,
+    /// First error from serving stdout
+    error: Option<anyhow::Error>,
+}
+
+impl Shared {
+    fn new(session: Session) -> Self {
+        Self(Arc::new(Mutex::new(SharedState { session, error: None })))
+    }
+}
+
+/// The module's stdin: queued input and syscall replies
+struct Stdin(Shared);
+
+impl Read for Stdin {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        Ok(self.0 .0.lock().unwrap().session.read_stdin(buf))
+    }
+}
+
+/// The module's stdout: every byte goes to the session
+struct Stdout(Shared);
+
+impl Write for Stdout {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.0 .0.lock().unwrap();
+        if let Err(error) = state.session.write_stdout(buf) {
+            let message = format!("{:#}", error);
+            state.error.get_or_insert(error);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+use crate::Framing;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Served by this host; must match the `rename`s below
+const HOST_SYSCALLS: &[&str] = &[
+    "syscall.abi",
+    "syscall.kv.set",
+    "syscall.kv.get",
+    "syscall.kv.delete",
+    "syscall.kv.list",
+];
+
+/// A request a module writes to stdout as one JSON line (docs/SYSCALLS.md)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Syscall {
+    /// Handshake asking both sides to switch to `framing` ("lines" or
+    /// "length") after the reply
+    #[serde(rename = "syscall.abi")]
+    Abi { framing: String },
+    #[serde(rename = "syscall.kv.set")]
+    KvSet { key: String, value: Value },
+    #[serde(rename = "syscall.kv.get")]
+    KvGet { key: String },
+    #[serde(rename = "syscall.kv.delete")]
+    KvDelete { key: String },
+    /// Keys starting with `prefix`; an absent prefix lists everything
+    #[serde(rename = "syscall.kv.list")]
+    KvList {
+        #[serde(default)]
+        prefix: String,
+    },
+    /// Any other `syscall.*` line, e.g. `syscall.http.fetch`; this host
+    /// does not serve it
+    #[serde(skip)]
+    Unsupported(String),
+}
+
+/// The host's answer to one `Syscall`, in the executor's `sysret.*` shape
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum SyscallResult {
+    /// `framing` is what both sides use from now on; `ok` is false and the
+    /// framing unchanged when the request named one the host does not know
+    #[serde(rename = "sysret.abi")]
+    Abi { ok: bool, framing: Framing },
+    #[serde(rename = "sysret.kv.set")]
+    KvSet { ok: bool, key: String },
+    /// `ok` is false and `value` null when the key was never set
+    #[serde(rename = "sysret.kv.get")]
+    KvGet { ok: bool, key: String, value: Value },
+    /// `ok` is false when there was nothing to delete
+    #[serde(rename = "sysret.kv.delete")]
+    KvDelete { ok: bool, key: String },
+    /// Matching keys, sorted
+    #[serde(rename = "sysret.kv.list")]
+    KvList { ok: bool, prefix: String, keys: Vec<String> },
+    #[serde(rename = "sysret.unsupported")]
+    Unsupported { syscall: String },
+}
+
+impl Syscall {
+    /// `None` for ordinary output: non-JSON messages and JSON that is not a
+    /// `syscall.*`. A served syscall with missing fields is an error.
+    pub fn parse(message: &str) -> Result<Option<Self>> {
+        let Ok(json) = serde_json::from_str::<Value>(message) else {
+            return Ok(None);
+        };
+        let Some(kind) = json.get("type").and_then(Value::as_str) else {
+            return Ok(None);
+        };
+        if !kind.starts_with("syscall.") {
+            return Ok(None);
+        }
+        if !HOST_SYSCALLS.contains(&kind) {
+            return Ok(Some(Self::Unsupported(kind.to_string())));
+        }
+
+        let kind = kind.to_string();
+        let call = serde_json::from_value(json).with_context(|| format!("Malformed {}", kind))?;
+        Ok(Some(call))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_lines() {
+        assert_eq!(
+            Syscall::parse(r#"{"type":"syscall.kv.set","key":"note/last","value":{"msg":"hi"}}"#).unwrap(),
+            Some(Syscall::KvSet {
+                key: "note/last".to_string(),
+                value: json!({ "msg": "hi" }),
+            })
+        );
+        assert_eq!(
+            Syscall::parse(r#"{"type":"syscall.kv.list"}"#).unwrap(),
+            Some(Syscall::KvList { prefix: String::new() })
+        );
+        assert_eq!(
+            Syscall::parse(r#"{"type":"syscall.emit","event":{}}"#).unwrap(),
+            Some(Syscall::Unsupported("syscall.emit".to_string()))
+        );
+
+        assert_eq!(Syscall::parse("hello").unwrap(), None);
+        assert_eq!(Syscall::parse(r#"{"type":"log","msg":"x"}"#).unwrap(), None);
+        assert!(Syscall::parse(r#"{"type":"syscall.kv.get"}"#).is_err());
+    }
+
+    #[test]
+    fn test_result_shape() {
+        let result = SyscallResult::KvGet {
+            ok: true,
+            key: "note/last".to_string(),
+            value: json!({ "msg": "hi" }),
+        };
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            json!({ "type": "sysret.kv.get", "ok": true, "key": "note/last", "value": { "msg": "hi" } })
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use void_wasm_host::{run_module_with, KvStore, SyscallResult};
+
+const TARGET: &str = "wasm32-wasip1";
+const KV_NOTE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../modules/kv-note/Cargo.toml");
+
+/// Build modules/kv-note for wasm32-wasip1; `None` if that target is not installed
+fn build_kv_note() -> Option<Vec<u8>> {
+    let libdir = Command::new("rustc")
+        .args(["--print", "target-libdir", "--target", TARGET])
+        .output()
+        .expect("run rustc");
+    if !libdir.status.success() || !Path::new(String::from_utf8_lossy(&libdir.stdout).trim()).exists() {
+        return None;
+    }
+
+    let target_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("kv-note");
+    let status = Command::new(env!("CARGO"))
+        .args(["build", "--release", "--target", TARGET, "--manifest-path", KV_NOTE])
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .status()
+        .expect("run cargo");
+    assert!(status.success(), "building kv-note failed");
+
+    let wasm = target_dir.join(TARGET).join("release/kv_note.wasm");
+    Some(std::fs::read(&wasm).unwrap_or_else(|e| panic!("{}: {}", wasm.display(), e)))
+}
+
+#[test]
+fn test_kv_note_end_to_end() {
+    let Some(wasm) = build_kv_note() else {
+        eprintln!("skipping: the {} target is not installed (rustup target add {})", TARGET, TARGET);
+        return;
+    };
+
+    let mut kv = KvStore::default();
+    let results = run_module_with(&mut kv, &wasm, b"{}").unwrap();
+
+    assert_eq!(
+        results,
+        vec![
+            SyscallResult::KvSet {
+                ok: true,
+                key: "note/last".to_string(),
+            },
+            SyscallResult::KvSet {
+                ok: true,
+                key: "note/draft".to_string(),
+            },
+            SyscallResult::KvList {
+                ok: true,
+                prefix: "note/".to_string(),
+                keys: vec!["note/draft".to_string(), "note/last".to_string()],
+            },
+            SyscallResult::KvDelete {
+                ok: true,
+                key: "note/draft".to_string(),
+            },
+            // Deleted keys read back as null
+            SyscallResult::KvGet {
+                ok: false,
+                key: "note/draft".to_string(),
+                value: serde_json::Value::Null,
+            },
+            SyscallResult::KvGet {
+                ok: true,
+                key: "note/last".to_string(),
+                value: json!({ "msg": "hello from wasm" }),
+            },
+            SyscallResult::KvSet {
+                ok: true,
+                key: "note/archive".to_string(),
+            },
+        ]
+    );
+    // Only reachable if the `get` reply made it back to the module
+    assert_eq!(kv.get("note/archive"), Some(&json!({ "msg": "hello from wasm" })));
+}
@@ -1,5 +1,14 @@
-use serde_json::json;
-use std::io::{self, Read};
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Read};
+
+/// Print one syscall and read the host's reply from stdin; `null` when
+/// the host sends none
+fn call(request: Value) -> Value {
+    println!("{}", request);
+    let mut reply = String::new();
+    io::stdin().lock().read_line(&mut reply).ok();
+    serde_json::from_str(&reply).unwrap_or(Value::Null)
+}
 
 #[no_mangle]
 pub extern "C" fn _start() {
@@ -7,15 +16,44 @@ pub extern "C" fn _start() {
     let mut buf = String::new();
     io::stdin().read_to_string(&mut buf).ok();
 
-    // set and then get a note
-    println!("{}", json!({
+    // set two notes and list them
+    call(json!({
         "type": "syscall.kv.set",
         "key": "note/last",
         "value": { "msg": "hello from wasm" }
-    }).to_string());
+    }));
+
+    call(json!({
+        "type": "syscall.kv.set",
+        "key": "note/draft",
+        "value": { "msg": "scratch" }
+    }));
+
+    call(json!({
+        "type": "syscall.kv.list",
+        "prefix": "note/"
+    }));
 
-    println!("{}", json!({
+    // drop the draft; reading it back yields null
+    call(json!({
+        "type": "syscall.kv.delete",
+        "key": "note/draft"
+    }));
+
+    call(json!({
+        "type": "syscall.kv.get",
+        "key": "note/draft"
+    }));
+
+    // archive what came back, so the value has to round-trip through stdin
+    let last = call(json!({
         "type": "syscall.kv.get",
         "key": "note/last"
-    }).to_string());
+    }));
+
+    call(json!({
+        "type": "syscall.kv.set",
+        "key": "note/archive",
+        "value": last["value"]
+    }));
 }